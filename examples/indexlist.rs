@@ -5,6 +5,7 @@
  */
 use index_list::IndexList;
 
+#[allow(clippy::option_map_unit_fn)]
 fn main() {
     let name = "IndexList";
     let mut list = IndexList::<String>::from(
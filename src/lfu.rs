@@ -0,0 +1,360 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the LfuCache type
+//!
+//! The cache keeps all its entries in one `IndexList`, grouped by how often
+//! they have been used, from the least to the most frequent, and within each
+//! group from the least to the most recent. A second `IndexList` holds one
+//! bucket per frequency in use, in the same order, with the indexes of the
+//! first and last entry of its group. Using an entry moves it to the end of
+//! the group for the next frequency, creating the bucket if needed, so every
+//! operation is a constant number of relinks. The entry at the beginning of
+//! the list is the one to evict.
+use std::{borrow::Borrow, collections::HashMap, fmt, hash::Hash, iter::Rev};
+use crate::{listindex::ListIndex, listiter::ListIter, IndexList};
+
+// the most entries to allocate room for before they are added
+const PREALLOC_MAX: usize = 1024;
+
+struct LfuEntry<K, V> {
+    key: K,
+    value: V,
+    bucket: ListIndex,
+}
+
+struct LfuBucket {
+    freq: u64,
+    first: ListIndex,
+    last: ListIndex,
+}
+
+/// A least frequently used cache with a fixed capacity, where ties are broken
+/// by evicting the least recently used entry.
+pub struct LfuCache<K, V> {
+    list: IndexList<LfuEntry<K, V>>,
+    buckets: IndexList<LfuBucket>,
+    map: HashMap<K, ListIndex>,
+    capacity: usize,
+    on_evict: Option<Box<dyn FnMut(K, V) + Send>>,
+}
+
+impl<K, V> LfuCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Creates a new empty cache that holds at most `capacity` entries.
+    ///
+    /// Room for at most 1024 entries is allocated up front, the rest is
+    /// allocated as the cache fills up.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::lfu::LfuCache;
+    ///
+    /// let cache = LfuCache::<u32, String>::new(100);
+    /// assert_eq!(cache.capacity(), 100);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        LfuCache {
+            list: IndexList::with_capacity(capacity.min(PREALLOC_MAX)),
+            buckets: IndexList::new(),
+            map: HashMap::with_capacity(capacity.min(PREALLOC_MAX)),
+            capacity,
+            on_evict: None,
+        }
+    }
+    /// Returns the maximum number of entries in the cache.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Returns the number of entries in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+    /// Returns true when the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+    /// Returns true if the key is in the cache, without marking it as used.
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+    /// Returns how many times the key has been used, counting the `put` that
+    /// inserted it.
+    pub fn frequency<Q>(&self, key: &Q) -> Option<u64>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        Some(self.buckets[self.list[index].bucket].freq)
+    }
+    /// Set a callback that is called with every entry that is evicted to make
+    /// room for a new one, or because the capacity was reduced.
+    ///
+    /// Entries that are removed explicitly, by `pop_lfu`, `remove` or `clear`,
+    /// are not passed to the callback.
+    pub fn set_eviction_callback<F>(&mut self, f: F)
+    where
+        F: FnMut(K, V) + Send + 'static,
+    {
+        self.on_evict = Some(Box::new(f));
+    }
+    /// Remove the eviction callback, if any.
+    #[inline]
+    pub fn clear_eviction_callback(&mut self) {
+        self.on_evict = None;
+    }
+    /// Get a reference to the value of the key and count it as used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.touch(index);
+        Some(&self.list[index].value)
+    }
+    /// Get a mutable reference to the value of the key and count it as used.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.touch(index);
+        Some(&mut self.list[index].value)
+    }
+    /// Get a reference to the value of the key, without counting it as used.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        Some(&self.list[index].value)
+    }
+    /// Get the least frequently used entry, the next one to be evicted.
+    #[inline]
+    pub fn peek_lfu(&self) -> Option<(&K, &V)> {
+        self.list.get(self.list.first_index()).map(|entry| (&entry.key, &entry.value))
+    }
+    /// Insert the value for the key and count it as used.
+    ///
+    /// If the key was already in the cache then its old value is returned.
+    /// Otherwise, if the cache is full, the least frequently used entry is
+    /// evicted first.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::lfu::LfuCache;
+    /// let mut cache = LfuCache::new(2);
+    /// cache.put("a", 1);
+    /// cache.put("b", 2);
+    /// cache.get("a");
+    /// cache.put("c", 3);
+    /// assert_eq!(cache.peek("b"), None);
+    /// assert_eq!(cache.frequency("a"), Some(2));
+    /// assert_eq!(cache.put("c", 4), Some(3));
+    /// assert_eq!(cache.frequency("c"), Some(2));
+    /// ```
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&index) = self.map.get(&key) {
+            self.touch(index);
+            return Some(std::mem::replace(&mut self.list[index].value, value));
+        }
+        if self.capacity == 0 {
+            self.evict(key, value);
+            return None;
+        }
+        self.shrink_to(self.capacity - 1);
+        let first = self.buckets.first_index();
+        let index = if self.buckets.get(first).is_some_and(|bucket| bucket.freq == 1) {
+            let index = self.list.insert_after(self.buckets[first].last, LfuEntry {
+                key: key.clone(),
+                value,
+                bucket: first,
+            });
+            self.buckets[first].last = index;
+            index
+        } else {
+            let bucket = self.buckets.insert_first(LfuBucket {
+                freq: 1,
+                first: ListIndex::new(),
+                last: ListIndex::new(),
+            });
+            let index = self.list.insert_first(LfuEntry { key: key.clone(), value, bucket });
+            self.buckets[bucket].first = index;
+            self.buckets[bucket].last = index;
+            index
+        };
+        self.map.insert(key, index);
+        None
+    }
+    /// Remove the least frequently used entry and return it.
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        let index = self.list.first_index();
+        self.unlink(index)?;
+        let entry = self.list.remove(index)?;
+        self.map.remove(&entry.key);
+        Some((entry.key, entry.value))
+    }
+    /// Remove the key from the cache and return its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.map.remove(key)?;
+        self.unlink(index)?;
+        self.list.remove(index).map(|entry| entry.value)
+    }
+    /// Change the capacity of the cache, evicting the least frequently used
+    /// entries that no longer fit.
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.shrink_to(capacity);
+    }
+    /// Remove all the entries, keeping the capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.buckets.clear();
+        self.map.clear();
+    }
+    /// Create an iterator over the entries, from the most to the least
+    /// frequently used, without counting them as used.
+    ///
+    /// Entries that are used equally often come from the most to the least
+    /// recently used.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::lfu::LfuCache;
+    /// let mut cache = LfuCache::new(3);
+    /// cache.extend([(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// cache.get(&1);
+    /// let keys: Vec<_> = cache.iter().map(|(key, _)| *key).collect();
+    /// assert_eq!(keys, vec![1, 3, 2]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> LfuIter<'_, K, V> {
+        LfuIter { iter: self.list.iter().rev() }
+    }
+
+    // move the entry to the end of the group for the next frequency
+    fn touch(&mut self, index: ListIndex) {
+        let bucket = self.list[index].bucket;
+        let freq = self.buckets[bucket].freq;
+        let next = self.buckets.next_index(bucket);
+        let target = if self.buckets.get(next).is_some_and(|b| b.freq == freq + 1) {
+            next
+        } else {
+            self.buckets.insert_after(bucket, LfuBucket {
+                freq: freq + 1,
+                first: ListIndex::new(),
+                last: ListIndex::new(),
+            })
+        };
+        // the group of the target bucket directly follows the current group
+        let after = match self.buckets[target].last {
+            last if last.is_some() => last,
+            _ => self.buckets[bucket].last,
+        };
+        self.unlink(index);
+        if after != index {
+            self.list.move_after(index, after);
+        }
+        let target_bucket = &mut self.buckets[target];
+        if target_bucket.first.is_none() {
+            target_bucket.first = index;
+        }
+        target_bucket.last = index;
+        self.list[index].bucket = target;
+    }
+    // detach the entry from its bucket, removing the bucket if it is left
+    // empty, before the entry is moved or removed
+    fn unlink(&mut self, index: ListIndex) -> Option<()> {
+        let bucket = self.list.get(index)?.bucket;
+        let (first, last) = (self.buckets[bucket].first, self.buckets[bucket].last);
+        if first == index && last == index {
+            self.buckets.remove(bucket);
+        } else if first == index {
+            self.buckets[bucket].first = self.list.next_index(index);
+        } else if last == index {
+            self.buckets[bucket].last = self.list.prev_index(index);
+        }
+        Some(())
+    }
+    fn shrink_to(&mut self, len: usize) {
+        while self.list.len() > len {
+            if let Some((key, value)) = self.pop_lfu() {
+                self.evict(key, value);
+            }
+        }
+    }
+    fn evict(&mut self, key: K, value: V) {
+        if let Some(f) = self.on_evict.as_mut() {
+            f(key, value);
+        }
+    }
+}
+
+impl<K, V> Extend<(K, V)> for LfuCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.put(key, value);
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for LfuCache<K, V>
+where
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the entries of an `LfuCache`, from the most to the least
+/// frequently used.
+pub struct LfuIter<'a, K, V> {
+    iter: Rev<ListIter<'a, LfuEntry<K, V>>>,
+}
+
+impl<'a, K, V> Iterator for LfuIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|entry| (&entry.key, &entry.value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for LfuIter<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for LfuIter<'_, K, V> {}
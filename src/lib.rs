@@ -15,27 +15,68 @@
 //!
 #![forbid(unsafe_code)]
 
+pub mod lfu;
+pub mod listarena;
+pub mod listcheck;
 pub mod listdrainiter;
+pub mod listentry;
+pub mod listfmt;
 pub mod listindex;
 pub mod listiter;
+pub mod listlabel;
+pub mod listlayout;
+pub mod listmap;
+pub mod listmulti;
+pub mod listpolicy;
+pub mod listposition;
+pub mod listrank;
+pub mod listset;
+pub mod listslotiter;
+pub mod listtree;
+pub mod liststats;
+pub mod lru;
+pub mod timer_wheel;
 mod listnode;
 mod listends;
 
-use std::{cmp::Ordering, default::Default, fmt};
-use std::iter::{Extend, FromIterator};
-use crate::{listnode::ListNode, listends::ListEnds};
+use std::{cmp::{Ordering, Reverse}, default::Default, fmt::{self, Write}, mem};
+use std::collections::TryReserveError;
+use std::iter::{Extend, FromIterator, Rev};
+use std::ops::{Index, IndexMut};
+use crate::{listnode::ListNode, listends::ListEnds, listpolicy::{PolicyState, Relocator}};
+pub use crate::listarena::{ArenaIter, IndexListArena, ListId};
+pub use crate::listcheck::{CorruptionReport, ListChain};
+pub use crate::listentry::{ListEntry, OccupiedListEntry, VacantListEntry};
+pub use crate::listfmt::{ListDisplay, ListLayout};
 pub use crate::listindex::ListIndex as ListIndex;
-pub use crate::listiter::ListIter as ListIter;
+pub use crate::listiter::{ListIndexedIter, ListIndexedIterMut, ListIndexes, ListIter, ListIterMut};
+pub use crate::listlabel::LabeledIndexList as LabeledIndexList;
+pub use crate::listlayout::ListSlot as ListSlot;
+pub use crate::listmap::{IndexListMap, MapEntry, MapIter, MapIterMut, OccupiedMapEntry, VacantMapEntry};
+pub use crate::listmulti::{MultiIndexList, MultiIndexes, MultiIter};
+pub use crate::listpolicy::{ReusePolicy, TrimPolicy, TrimStats};
+pub use crate::listposition::ListPosition as ListPosition;
+pub use crate::listrank::RankedIndexList as RankedIndexList;
+pub use crate::listset::{IndexListSet, SetIter};
+pub use crate::listslotiter::{ListSlotIter, ListSlotIterMut};
+pub use crate::liststats::ListStats as ListStats;
+pub use crate::listtree::{
+    IndexTree, TreeAncestors, TreeBreadthFirst, TreePostorder, TreePreorder, TreeSiblings,
+};
 pub use crate::listdrainiter::ListDrainIter as ListDrainIter;
+pub use crate::lfu::LfuCache as LfuCache;
+pub use crate::lru::LruCache as LruCache;
+pub use crate::timer_wheel::{TimerHandle, TimerWheel};
 
 /// Doubly-linked list implemented in safe Rust.
-#[derive(Debug)]
 pub struct IndexList<T> {
     elems: Vec<Option<T>>,
     nodes: Vec<ListNode>,
     used: ListEnds,
     free: ListEnds,
     size: usize,
+    // only allocated once a policy other than the default is set
+    policy: Option<Box<PolicyState>>,
 }
 
 impl<T> Default for IndexList<T> {
@@ -46,6 +87,7 @@ impl<T> Default for IndexList<T> {
             used: ListEnds::new(),
             free: ListEnds::new(),
             size: 0,
+            policy: None,
         }
     }
 }
@@ -64,6 +106,149 @@ impl<T> IndexList<T> {
     pub fn new() -> Self {
         Default::default()
     }
+    /// Creates a new empty index list, with room for at least `capacity`
+    /// elements before it needs to allocate more memory.
+    ///
+    /// *NOTE* that `capacity` still returns zero, as no indexes are in use.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::IndexList;
+    ///
+    /// let list = IndexList::<u64>::with_capacity(100);
+    /// assert_eq!(list.capacity(), 0);
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexList::<T> {
+            elems: Vec::with_capacity(capacity),
+            nodes: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+    /// Creates a new empty index list, which reuses unused indexes in the
+    /// order given by the policy.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::{IndexList, ReusePolicy};
+    ///
+    /// let list = IndexList::<u64>::with_reuse_policy(ReusePolicy::Lifo);
+    /// ```
+    #[inline]
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        let mut list = IndexList::new();
+        list.set_reuse_policy(policy);
+        list
+    }
+    /// Returns the policy for reusing unused indexes.
+    #[inline]
+    pub fn reuse_policy(&self) -> ReusePolicy {
+        self.policy.as_ref().map_or_else(ReusePolicy::default, |policy| policy.reuse)
+    }
+    /// Change the policy for reusing unused indexes.
+    ///
+    /// The default policy is `ReusePolicy::Fifo`.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, ReusePolicy};
+    /// # let mut list = IndexList::from(&mut vec![1, 2, 3, 4]);
+    /// list.set_reuse_policy(ReusePolicy::LowestFirst);
+    /// let (first, last) = (list.first_index(), list.last_index());
+    /// list.remove(last);
+    /// list.remove(first);
+    /// assert_eq!(list.insert_last(5), first);
+    /// ```
+    pub fn set_reuse_policy(&mut self, policy: ReusePolicy) {
+        if self.policy.is_none() && policy == ReusePolicy::default() {
+            return;
+        }
+        let state = self.policy.get_or_insert_with(Default::default);
+        state.reuse = policy;
+        state.lowest_free.clear();
+        if policy == ReusePolicy::LowestFirst {
+            let mut index = self.free.head;
+            while let Some(at) = index.get() {
+                state.lowest_free.push(Reverse(at));
+                index = self.nodes[at].next;
+            }
+        }
+    }
+    /// Returns the policy for trimming the list automatically.
+    #[inline]
+    pub fn trim_policy(&self) -> TrimPolicy {
+        self.policy.as_ref().map_or_else(TrimPolicy::default, |policy| policy.trim)
+    }
+    /// Change the policy for trimming the list automatically, whenever an
+    /// element is removed.
+    ///
+    /// The default policy is `TrimPolicy::Manual`, which never trims. The
+    /// `min_load` of the other policies is kept within (0, 1]: a value above 1
+    /// is lowered to 1, while zero, a negative value or NaN would never
+    /// trigger and sets `TrimPolicy::Manual` instead.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, TrimPolicy};
+    /// # let mut list = IndexList::from(&mut vec![4, 8, 15, 16, 23, 42]);
+    /// list.set_trim_policy(TrimPolicy::Safe { min_load: 0.5 });
+    /// list.remove_last();
+    /// list.remove_last();
+    /// assert_eq!(list.capacity(), 6);
+    /// list.remove_last();
+    /// list.remove_last();
+    /// assert_eq!(list.capacity(), 2);
+    /// assert_eq!(list.trim_stats().runs, 1);
+    /// list.set_trim_policy(TrimPolicy::Compact { min_load: f32::NAN });
+    /// assert_eq!(list.trim_policy(), TrimPolicy::Manual);
+    /// ```
+    #[inline]
+    pub fn set_trim_policy(&mut self, policy: TrimPolicy) {
+        let policy = policy.clamped();
+        if self.policy.is_some() || policy != TrimPolicy::default() {
+            self.policy_mut().trim = policy;
+        }
+    }
+    /// Returns the counters for how often, and how much, the list has been
+    /// trimmed automatically.
+    #[inline]
+    pub fn trim_stats(&self) -> TrimStats {
+        self.policy.as_ref().map_or_else(TrimStats::default, |policy| policy.trim_stats)
+    }
+    /// Set a callback that is called with the old and the new index of every
+    /// element that is moved by `trim_swap`, either when called directly or by
+    /// the `TrimPolicy::Compact` policy.
+    ///
+    /// The callback must be `Send`, so that the list stays `Send` and `Sync`.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, ListIndex};
+    /// # use std::sync::mpsc::channel;
+    /// # let mut list = IndexList::from(&mut vec![4, 8, 15]);
+    /// let (log, moved) = channel();
+    /// list.set_relocation_callback(move |old, new| {
+    ///     log.send((old, new)).unwrap();
+    /// });
+    /// let last = list.last_index();
+    /// list.remove_first();
+    /// list.trim_swap();
+    /// assert_eq!(moved.try_iter().collect::<Vec<_>>(), vec![(last, list.last_index())]);
+    /// ```
+    pub fn set_relocation_callback<F>(&mut self, f: F)
+    where
+        F: FnMut(ListIndex, ListIndex) + Send + 'static,
+    {
+        self.policy_mut().relocate = Some(Relocator::new(f));
+    }
+    /// Remove the relocation callback, if any.
+    #[inline]
+    pub fn clear_relocation_callback(&mut self) {
+        if let Some(policy) = self.policy.as_mut() {
+            policy.relocate = None;
+        }
+    }
     /// Returns the current capacity of the list.
     ///
     /// This value is always greater than or equal to the length.
@@ -95,6 +280,285 @@ impl<T> IndexList<T> {
     pub fn len(&self) -> usize {
         self.size
     }
+    /// Collect statistics about the memory usage and fragmentation of the
+    /// list.
+    ///
+    /// This walks both the list and the unused indexes, so it takes time in
+    /// proportion to the capacity.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![4, 8, 15, 16, 23, 42]);
+    /// list.remove_first();
+    /// list.remove_last();
+    /// let stats = list.stats();
+    /// assert_eq!((stats.used, stats.free, stats.trailing_free), (4, 2, 1));
+    /// assert_eq!(stats.fragmentation, 1.0 / 6.0);
+    /// assert_eq!(stats.neighbour_distance, 1.0);
+    /// ```
+    pub fn stats(&self) -> ListStats {
+        let mut free = 0;
+        let mut index = self.free.head;
+        while let Some(at) = index.get() {
+            free += 1;
+            index = self.nodes[at].next;
+        }
+        let trailing_free = (0..self.capacity())
+            .rev()
+            .take_while(|&i| self.is_free(i) && !self.is_reserved(i))
+            .count();
+        let mut distance = 0;
+        let mut index = self.first_index();
+        while let (Some(this), Some(next)) = (index.get(), self.next_index(index).get()) {
+            distance += this.abs_diff(next);
+            index = self.next_index(index);
+        }
+        let ratio = |part: usize, whole: usize| {
+            if whole == 0 { 0.0 } else { part as f64 / whole as f64 }
+        };
+        ListStats {
+            used: self.len(),
+            free,
+            reserved: self.capacity() - self.len() - free,
+            capacity: self.capacity(),
+            trailing_free,
+            fragmentation: ratio(free - trailing_free, self.capacity()),
+            elems_bytes: self.elems.capacity() * mem::size_of::<Option<T>>(),
+            nodes_bytes: self.nodes.capacity() * mem::size_of::<ListNode>(),
+            neighbour_distance: ratio(distance, self.len().saturating_sub(1)),
+        }
+    }
+    /// Check that the internal links of the list are consistent.
+    ///
+    /// Both the chain of elements and the chain of unused indexes are walked
+    /// to verify that every link is matched by a link back, that the ends of
+    /// each chain are correct, that there are no cycles, that every index is
+    /// on exactly one chain (unless reserved) with data only when used, and
+    /// that the length matches. The first problem found is reported.
+    ///
+    /// With the `validate` feature enabled, this is checked after every
+    /// change to the list in debug builds.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![4, 8, 15, 16, 23, 42]);
+    /// list.remove_first();
+    /// assert!(list.validate().is_ok());
+    /// ```
+    pub fn validate(&self) -> Result<(), CorruptionReport> {
+        if self.elems.len() != self.nodes.len() {
+            return Err(CorruptionReport::LengthMismatch {
+                elems: self.elems.len(),
+                nodes: self.nodes.len(),
+            });
+        }
+        let mut seen: Vec<Option<ListChain>> = vec![None; self.capacity()];
+        let counted = self.validate_chain(ListChain::Used, &self.used, &mut seen)?;
+        self.validate_chain(ListChain::Free, &self.free, &mut seen)?;
+        if let Some(slot) = (0..self.capacity()).find(|&i| {
+            seen[i].is_none() && !(self.is_reserved(i) && self.nodes[i].next.is_none())
+        }) {
+            return Err(CorruptionReport::Unlinked { index: ListIndex::from(slot) });
+        }
+        if counted != self.size {
+            return Err(CorruptionReport::SizeMismatch { size: self.size, counted });
+        }
+        Ok(())
+    }
+    /// Create a formatting adapter that shows the internal vectors and chains
+    /// of the list with `{:?}`, rather than the elements in order.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let list = IndexList::from(&mut vec![1, 2]);
+    /// let layout = format!("{:?}", list.debug_layout());
+    /// assert!(layout.starts_with("IndexList { elems: [Some(1), Some(2)], nodes: ["));
+    /// ```
+    #[inline]
+    pub fn debug_layout(&self) -> ListLayout<'_, T> {
+        ListLayout(self)
+    }
+    /// Create a formatting adapter that shows the elements in order, with a
+    /// configurable separator and optionally their indexes.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let list = IndexList::from(&mut vec!["A", "B", "C"]);
+    /// assert_eq!(list.display().to_string(), "[A >< B >< C]");
+    /// assert_eq!(list.display().separator(", ").to_string(), "[A, B, C]");
+    /// assert_eq!(list.display().indexes(true).separator(" ").to_string(),
+    ///            "[1:A 2:B 3:C]");
+    /// ```
+    #[inline]
+    pub fn display(&self) -> ListDisplay<'_, T> {
+        ListDisplay::new(self)
+    }
+    /// Describe the internal state of every index in the list, in index
+    /// order.
+    ///
+    /// The walk of a chain stops at a link that leads back to a slot already
+    /// walked, or out of bounds, so it ends even if the list is corrupt. The
+    /// slot with that link is marked as `cut_off`.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, ListChain};
+    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C"]);
+    /// list.remove_first();
+    /// let slots = list.layout();
+    /// assert_eq!(slots[0].chain, Some(ListChain::Free));
+    /// assert_eq!(slots[1].next, slots[2].index);
+    /// assert_eq!(slots[2].to_string(), "3: used data |<>2");
+    /// ```
+    pub fn layout(&self) -> Vec<ListSlot> {
+        let mut chains: Vec<Option<ListChain>> = vec![None; self.capacity()];
+        let mut cut_off = vec![false; self.capacity()];
+        for (chain, ends) in [(ListChain::Used, &self.used), (ListChain::Free, &self.free)] {
+            let (mut index, mut last) = (ends.head, None);
+            // every slot is walked at most once, so at most capacity steps
+            while let Some(at) = index.get() {
+                if at >= self.capacity() || chains[at].is_some() {
+                    if let Some(last) = last {
+                        cut_off[last] = true;
+                    }
+                    break;
+                }
+                chains[at] = Some(chain);
+                last = Some(at);
+                index = self.nodes[at].next;
+            }
+        }
+        chains.into_iter()
+            .zip(cut_off)
+            .enumerate()
+            .map(|(at, (chain, cut_off))| ListSlot {
+                index: ListIndex::from(at),
+                occupied: self.is_used(at),
+                next: self.nodes[at].next,
+                prev: self.nodes[at].prev,
+                chain,
+                cut_off,
+            })
+            .collect()
+    }
+    /// Render the chain of elements and the chain of unused indexes as a
+    /// Graphviz digraph.
+    ///
+    /// The chains are walked as by `layout`, and a link where a walk was cut
+    /// off is followed by a red "cut off" marker.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "B"]);
+    /// let dot = list.to_dot();
+    /// assert!(dot.starts_with("digraph IndexList {"));
+    /// assert!(dot.contains("n1 -> n2"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph IndexList {\n    rankdir=LR;\n");
+        let slots = self.layout();
+        for (chain, ends, color) in [
+            (ListChain::Used, &self.used, "black"),
+            (ListChain::Free, &self.free, "gray"),
+        ] {
+            let _ = writeln!(dot, "    subgraph cluster_{} {{", chain);
+            let _ = writeln!(dot, "        label=\"{}\"; color={};", chain, color);
+            for slot in slots.iter().filter(|slot| slot.chain == Some(chain)) {
+                let _ = writeln!(dot, "        n{0} [label=\"{0}\", color={1}];", slot.index, color);
+            }
+            let _ = writeln!(dot, "    }}");
+            if ends.head.is_some() {
+                let _ = writeln!(dot, "    {0}_head [shape=plaintext, label=\"{0} head\"];", chain);
+                let _ = writeln!(dot, "    {}_head -> n{} [color={}];", chain, ends.head, color);
+                let _ = writeln!(dot, "    {0}_tail [shape=plaintext, label=\"{0} tail\"];", chain);
+                let _ = writeln!(dot, "    n{} -> {}_tail [color={}];", ends.tail, chain, color);
+            }
+            for slot in slots.iter().filter(|slot| slot.chain == Some(chain)) {
+                if slot.next.is_some() {
+                    let _ = writeln!(dot, "    n{} -> n{} [color={}];", slot.index, slot.next, color);
+                }
+                if slot.cut_off {
+                    let _ = writeln!(dot, "    {0}_cut_off [shape=plaintext, label=\"cut off\", fontcolor=red];", chain);
+                    let _ = writeln!(dot, "    n{} -> {}_cut_off [color=red];", slot.index, chain);
+                }
+            }
+        }
+        for slot in slots.iter().filter(|slot| slot.chain.is_none()) {
+            let _ = writeln!(dot, "    n{0} [label=\"{0} reserved\", style=dashed];", slot.index);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+    /// Reserve memory for at least `additional` more elements, so that they
+    /// can be inserted without reallocating.
+    ///
+    /// Unused indexes are counted as room for new elements.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::<u64>::new();
+    /// list.reserve(1000);
+    /// (0..1000).for_each(|n| { list.insert_last(n); });
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        let extra = self.extra_slots(additional);
+        self.elems.reserve(extra);
+        self.nodes.reserve(extra);
+    }
+    /// Reserve memory for exactly `additional` more elements, without
+    /// deliberately over-allocating.
+    ///
+    /// Unused indexes are counted as room for new elements.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::<u64>::new();
+    /// list.reserve_exact(10);
+    /// ```
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let extra = self.extra_slots(additional);
+        self.elems.reserve_exact(extra);
+        self.nodes.reserve_exact(extra);
+    }
+    /// Try to reserve memory for at least `additional` more elements,
+    /// returning an error instead of aborting if the allocation fails.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::<u64>::new();
+    /// assert!(list.try_reserve(1000).is_ok());
+    /// assert!(list.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let extra = self.extra_slots(additional);
+        self.elems.try_reserve(extra)?;
+        self.nodes.try_reserve(extra)
+    }
+    /// Release as much memory as possible without changing any valid index.
+    ///
+    /// This calls `trim_safe` and then shrinks the allocated memory to fit.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::<u64>::with_capacity(100);
+    /// list.insert_last(42);
+    /// list.shrink_to_fit();
+    /// assert_eq!(list.capacity(), 1);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.trim_safe();
+        self.elems.shrink_to_fit();
+        self.nodes.shrink_to_fit();
+    }
     /// Clears the list be removing all elements, making it empty.
     ///
     /// Example:
@@ -111,6 +575,9 @@ impl<T> IndexList<T> {
         self.used.clear();
         self.free.clear();
         self.size = 0;
+        if let Some(policy) = self.policy.as_mut() {
+            policy.lowest_free.clear();
+        }
     }
     /// Returns `true` when the list is empty.
     ///
@@ -346,6 +813,136 @@ impl<T> IndexList<T> {
         }
         None
     }
+    /// Get mutable references to the element data at two different indexes.
+    ///
+    /// Returns `None` if either index is not in use or if they are the same.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![1, 2, 3]);
+    /// # let (first, last) = (list.first_index(), list.last_index());
+    /// if let Some((a, b)) = list.get_pair_mut(first, last) {
+    ///     std::mem::swap(a, b);
+    /// }
+    /// # assert_eq!(list.to_string(), "[3 >< 2 >< 1]");
+    /// ```
+    #[inline]
+    pub fn get_pair_mut(&mut self, this: ListIndex, that: ListIndex)
+        -> Option<(&mut T, &mut T)>
+    {
+        let [here, there] = self.get_many_mut([this, that])?;
+        Some((here, there))
+    }
+    /// Get mutable references to the element data at several indexes at once.
+    ///
+    /// The references are returned in the same order as the indexes. Returns
+    /// `None` if any index is not in use or if any index appears more than
+    /// once.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![1, 2, 3]);
+    /// let first = list.first_index();
+    /// let second = list.next_index(first);
+    /// let third = list.next_index(second);
+    /// if let Some([a, b, c]) = list.get_many_mut([third, first, second]) {
+    ///     *a += *b + *c;
+    /// }
+    /// # assert_eq!(list.to_string(), "[1 >< 2 >< 6]");
+    /// assert!(list.get_many_mut([first, first]).is_none());
+    /// ```
+    pub fn get_many_mut<const N: usize>(&mut self, indexes: [ListIndex; N])
+        -> Option<[&mut T; N]>
+    {
+        // pair each position in storage with its place in the result
+        let mut order: [(usize, usize); N] = [(0, 0); N];
+        for (place, index) in indexes.iter().enumerate() {
+            if !self.is_index_used(*index) {
+                return None;
+            }
+            order[place] = (index.get()?, place);
+        }
+        order.sort_unstable();
+        if order.windows(2).any(|w| w[0].0 == w[1].0) {
+            return None;
+        }
+        // split off each element in storage order to borrow them disjointly
+        let mut found: [Option<&mut T>; N] = [(); N].map(|_| None);
+        let mut rest: &mut [Option<T>] = &mut self.elems;
+        let mut offset = 0;
+        for (at, place) in order {
+            let (elem, tail) = mem::take(&mut rest)[at - offset..]
+                .split_first_mut()?;
+            found[place] = elem.as_mut();
+            rest = tail;
+            offset = at + 1;
+        }
+        Some(found.map(|elem| elem.unwrap()))
+    }
+    /// Get an entry for the index, to inspect or modify it in place.
+    ///
+    /// The entry is occupied when the index refers to an element in the list
+    /// and vacant otherwise.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, ListEntry};
+    /// # let mut list = IndexList::from(&mut vec![1, 2, 3]);
+    /// # let index = list.first_index();
+    /// if let ListEntry::Occupied(mut entry) = list.entry(index) {
+    ///     *entry.get_mut() += 10;
+    ///     entry.insert_after(12);
+    /// }
+    /// # assert_eq!(list.to_string(), "[11 >< 12 >< 2 >< 3]");
+    /// ```
+    #[inline]
+    pub fn entry(&mut self, index: ListIndex) -> ListEntry<'_, T> {
+        if self.is_index_used(index) {
+            ListEntry::Occupied(OccupiedListEntry::new(self, index))
+        } else {
+            ListEntry::Vacant(VacantListEntry::new(self, index))
+        }
+    }
+    /// Replace the element data at the index and return the old data.
+    ///
+    /// Returns `None`, and drops the new data, if the index is not in use.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C"]);
+    /// # let index = list.last_index();
+    /// let old = list.replace(index, "D");
+    /// # assert_eq!(old, Some("C"));
+    /// # assert_eq!(list.to_string(), "[A >< B >< D]");
+    /// ```
+    #[inline]
+    pub fn replace(&mut self, index: ListIndex, elem: T) -> Option<T> {
+        self.get_mut(index).map(|data| mem::replace(data, elem))
+    }
+    /// Take the element data at the index, leaving the default value in its
+    /// place.
+    ///
+    /// The element remains in the list, unlike when calling `remove`.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![4, 8, 15]);
+    /// # let index = list.first_index();
+    /// let data = list.take(index);
+    /// # assert_eq!(data, Some(4));
+    /// # assert_eq!(list.to_string(), "[0 >< 8 >< 15]");
+    /// ```
+    #[inline]
+    pub fn take(&mut self, index: ListIndex) -> Option<T>
+    where
+        T: Default,
+    {
+        self.get_mut(index).map(mem::take)
+    }
     /// Swap the element data between two indexes.
     ///
     /// Both indexes must be valid.
@@ -406,6 +1003,9 @@ impl<T> IndexList<T> {
     }
     /// Returns `true` if the element is in the list.
     ///
+    /// This searches all the elements, see `IndexListSet` for a constant time
+    /// alternative.
+    ///
     /// Example:
     /// ```rust
     /// # use index_list::IndexList;
@@ -427,7 +1027,8 @@ impl<T> IndexList<T> {
     /// Returns the index of the element containg the data.
     ///
     /// If there is more than one element with the same data, the one with the
-    /// lowest index will always be returned.
+    /// lowest index will always be returned. This searches all the elements,
+    /// see `IndexListSet` for a constant time alternative.
     ///
     /// Example:
     /// ```rust
@@ -463,6 +1064,7 @@ impl<T> IndexList<T> {
     pub fn insert_first(&mut self, elem: T) -> ListIndex {
         let this = self.new_node(Some(elem));
         self.linkin_first(this);
+        self.debug_validate();
         this
     }
     /// Insert a new element at the end.
@@ -479,6 +1081,7 @@ impl<T> IndexList<T> {
     pub fn insert_last(&mut self, elem: T) -> ListIndex {
         let this = self.new_node(Some(elem));
         self.linkin_last(this);
+        self.debug_validate();
         this
     }
     /// Insert a new element before the index.
@@ -498,6 +1101,7 @@ impl<T> IndexList<T> {
         }
         let this = self.new_node(Some(elem));
         self.linkin_this_before_that(this, index);
+        self.debug_validate();
         this
     }
     /// Insert a new element after the index.
@@ -517,51 +1121,307 @@ impl<T> IndexList<T> {
         }
         let this = self.new_node(Some(elem));
         self.linkin_this_after_that(this, index);
+        self.debug_validate();
         this
     }
-    /// Remove the first element and return its data.
+    /// Insert a new element at the beginning, created by a closure that is
+    /// given the index of the new element.
     ///
     /// Example:
     /// ```rust
-    /// # use index_list::IndexList;
-    /// # let mut list = IndexList::<u64>::new();
-    /// # list.insert_first(42);
-    /// let data = list.remove_first();
-    /// # assert_eq!(data, Some(42));
+    /// # use index_list::{IndexList, ListIndex};
+    /// # let mut list = IndexList::<(ListIndex, u64)>::new();
+    /// let index = list.insert_first_with(|me| (me, 42));
+    /// assert_eq!(list.get(index), Some(&(index, 42)));
     /// ```
-    pub fn remove_first(&mut self) -> Option<T> {
-        self.remove(self.first_index())
+    pub fn insert_first_with<F>(&mut self, f: F) -> ListIndex
+    where
+        F: FnOnce(ListIndex) -> T,
+    {
+        let this = self.new_node_with(f);
+        self.linkin_first(this);
+        self.debug_validate();
+        this
     }
-    /// Remove the last element and return its data.
+    /// Insert a new element at the end, created by a closure that is given the
+    /// index of the new element.
     ///
     /// Example:
     /// ```rust
-    /// # use index_list::IndexList;
-    /// # let mut list = IndexList::<u64>::new();
-    /// # list.insert_last(42);
-    /// let data = list.remove_last();
-    /// # assert_eq!(data, Some(42));
+    /// # use index_list::{IndexList, ListIndex};
+    /// # let mut list = IndexList::<(ListIndex, u64)>::new();
+    /// let index = list.insert_last_with(|me| (me, 42));
+    /// assert_eq!(list.get(index), Some(&(index, 42)));
     /// ```
-    pub fn remove_last(&mut self) -> Option<T> {
-        self.remove(self.last_index())
+    pub fn insert_last_with<F>(&mut self, f: F) -> ListIndex
+    where
+        F: FnOnce(ListIndex) -> T,
+    {
+        let this = self.new_node_with(f);
+        self.linkin_last(this);
+        self.debug_validate();
+        this
     }
-    /// Remove the element at the index and return its data.
+    /// Insert a new element before the index, created by a closure that is
+    /// given the index of the new element.
+    ///
+    /// If the index is `None` then the new element will be inserted first.
     ///
     /// Example:
     /// ```rust
-    /// # use index_list::IndexList;
-    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C"]);
-    /// # let mut index = list.first_index();
-    /// # index = list.next_index(index);
-    /// let data = list.remove(index);
-    /// # assert_eq!(data, Some("B"));
+    /// # use index_list::{IndexList, ListIndex};
+    /// # let mut list = IndexList::<(ListIndex, ListIndex)>::new();
+    /// let next = list.insert_last_with(|me| (me, ListIndex::new()));
+    /// let index = list.insert_before_with(next, |me| (me, next));
+    /// assert_eq!(list.get(index), Some(&(index, next)));
     /// ```
-    pub fn remove(&mut self, index: ListIndex) -> Option<T> {
-        let elem_opt = self.remove_elem_at_index(index);
-        if elem_opt.is_some() {
-            self.linkout_used(index);
+    pub fn insert_before_with<F>(&mut self, index: ListIndex, f: F) -> ListIndex
+    where
+        F: FnOnce(ListIndex) -> T,
+    {
+        if index.is_none() {
+            return self.insert_first_with(f);
+        }
+        let this = self.new_node_with(f);
+        self.linkin_this_before_that(this, index);
+        self.debug_validate();
+        this
+    }
+    /// Insert a new element after the index, created by a closure that is
+    /// given the index of the new element.
+    ///
+    /// If the index is `None` then the new element will be inserted last.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, ListIndex};
+    /// # let mut list = IndexList::<(ListIndex, ListIndex)>::new();
+    /// let prev = list.insert_last_with(|me| (me, ListIndex::new()));
+    /// let index = list.insert_after_with(prev, |me| (me, prev));
+    /// assert_eq!(list.get(index), Some(&(index, prev)));
+    /// ```
+    pub fn insert_after_with<F>(&mut self, index: ListIndex, f: F) -> ListIndex
+    where
+        F: FnOnce(ListIndex) -> T,
+    {
+        if index.is_none() {
+            return self.insert_last_with(f);
+        }
+        let this = self.new_node_with(f);
+        self.linkin_this_after_that(this, index);
+        self.debug_validate();
+        this
+    }
+    /// Insert all the elements from the iterator at the end, and return their
+    /// indexes in the same order.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![1, 2]);
+    /// let indexes = list.extend_returning_indexes(vec![3, 4, 5]);
+    /// assert_eq!(list.get(indexes[1]), Some(&4));
+    /// # assert_eq!(list.to_string(), "[1 >< 2 >< 3 >< 4 >< 5]");
+    /// ```
+    pub fn extend_returning_indexes<I>(&mut self, iter: I) -> Vec<ListIndex>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.insert_many_after(ListIndex::new(), iter)
+    }
+    /// Insert all the elements from the iterator after the index, keeping
+    /// their order, and return their indexes in the same order.
+    ///
+    /// If the index is `None` then the elements will be inserted last.
+    ///
+    /// Panics if the index is some other index that is not in use.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "D"]);
+    /// let indexes = list.insert_many_after(list.first_index(), vec!["B", "C"]);
+    /// assert_eq!(list.to_string(), "[A >< B >< C >< D]");
+    /// assert_eq!(indexes.len(), 2);
+    /// ```
+    pub fn insert_many_after<I>(&mut self, index: ListIndex, iter: I) -> Vec<ListIndex>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        assert!(
+            index.is_none() || self.is_index_used(index),
+            "index {} is not in use",
+            index
+        );
+        let prev = if index.is_none() { self.last_index() } else { index };
+        let mut indexes = Vec::new();
+        self.insert_run(prev, ListIndex::new(), iter, |this| indexes.push(this));
+        indexes
+    }
+    /// Insert all the elements from the iterator before the index, keeping
+    /// their order, and return their indexes in the same order.
+    ///
+    /// If the index is `None` then the elements will be inserted first.
+    ///
+    /// Panics if the index is some other index that is not in use.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "D"]);
+    /// let indexes = list.insert_many_before(list.last_index(), vec!["B", "C"]);
+    /// assert_eq!(list.to_string(), "[A >< B >< C >< D]");
+    /// assert_eq!(indexes.len(), 2);
+    /// ```
+    pub fn insert_many_before<I>(&mut self, index: ListIndex, iter: I) -> Vec<ListIndex>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        assert!(
+            index.is_none() || self.is_index_used(index),
+            "index {} is not in use",
+            index
+        );
+        let next = if index.is_none() { self.first_index() } else { index };
+        let mut indexes = Vec::new();
+        self.insert_run(ListIndex::new(), next, iter, |this| indexes.push(this));
+        indexes
+    }
+    /// Reserve an index for an element that will be inserted later.
+    ///
+    /// The reserved index is not part of the list, so it is neither counted
+    /// by `len` nor visited when walking the list, until it is filled with
+    /// `fill_reserved`. A reservation that is no longer needed should be given
+    /// back with `release_reserved`.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, ListIndex, ListPosition};
+    /// # let mut list = IndexList::<(u64, ListIndex)>::new();
+    /// let later = list.reserve_index();
+    /// let index = list.insert_last((1, later));
+    /// assert_eq!(list.len(), 1);
+    /// assert!(list.fill_reserved(later, (2, index), ListPosition::Last).is_ok());
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    pub fn reserve_index(&mut self) -> ListIndex {
+        let this = self.new_node(None);
+        self.size -= 1;
+        self.debug_validate();
+        this
+    }
+    /// Returns `true` if the index has been reserved, but not yet filled.
+    #[inline]
+    pub fn is_index_reserved(&self, index: ListIndex) -> bool {
+        match index.get() {
+            Some(at) if at < self.capacity() => self.is_reserved(at),
+            _ => false,
+        }
+    }
+    /// Store the element at a reserved index and link it in at the position.
+    ///
+    /// If the index is not reserved, or the position refers to an index that
+    /// is not in use, then the element is given back as an error and the list
+    /// is left unchanged.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, ListPosition};
+    /// # let mut list = IndexList::from(&mut vec!["A", "C"]);
+    /// let index = list.reserve_index();
+    /// let before = list.last_index();
+    /// list.fill_reserved(index, "B", ListPosition::Before(before)).unwrap();
+    /// assert_eq!(list.to_string(), "[A >< B >< C]");
+    /// assert_eq!(list.fill_reserved(index, "D", ListPosition::Last), Err("D"));
+    /// ```
+    pub fn fill_reserved(&mut self, index: ListIndex, elem: T, position: ListPosition)
+        -> Result<(), T>
+    {
+        let anchor = match position {
+            ListPosition::Before(that) | ListPosition::After(that) => that,
+            ListPosition::First | ListPosition::Last => ListIndex::new(),
+        };
+        if !self.is_index_reserved(index)
+            || (anchor.is_some() && !self.is_index_used(anchor))
+        {
+            return Err(elem);
+        }
+        self.insert_elem_at_index(index, Some(elem));
+        match position {
+            ListPosition::Before(that) if that.is_some() => {
+                self.linkin_this_before_that(index, that)
+            }
+            ListPosition::After(that) if that.is_some() => {
+                self.linkin_this_after_that(index, that)
+            }
+            ListPosition::First | ListPosition::Before(_) => self.linkin_first(index),
+            ListPosition::Last | ListPosition::After(_) => self.linkin_last(index),
+        }
+        self.debug_validate();
+        Ok(())
+    }
+    /// Give back a reserved index that will not be filled, so that it can be
+    /// reused.
+    ///
+    /// Nothing happens if the index is not reserved.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::<u64>::new();
+    /// let index = list.reserve_index();
+    /// list.release_reserved(index);
+    /// assert_eq!(list.insert_last(42), index);
+    /// ```
+    pub fn release_reserved(&mut self, index: ListIndex) {
+        if self.is_index_reserved(index) {
             self.linkin_free(index);
         }
+        self.debug_validate();
+    }
+    /// Remove the first element and return its data.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::<u64>::new();
+    /// # list.insert_first(42);
+    /// let data = list.remove_first();
+    /// # assert_eq!(data, Some(42));
+    /// ```
+    pub fn remove_first(&mut self) -> Option<T> {
+        self.remove(self.first_index())
+    }
+    /// Remove the last element and return its data.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::<u64>::new();
+    /// # list.insert_last(42);
+    /// let data = list.remove_last();
+    /// # assert_eq!(data, Some(42));
+    /// ```
+    pub fn remove_last(&mut self) -> Option<T> {
+        self.remove(self.last_index())
+    }
+    /// Remove the element at the index and return its data.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C"]);
+    /// # let mut index = list.first_index();
+    /// # index = list.next_index(index);
+    /// let data = list.remove(index);
+    /// # assert_eq!(data, Some("B"));
+    /// ```
+    pub fn remove(&mut self, index: ListIndex) -> Option<T> {
+        let elem_opt = self.remove_node(index);
+        if elem_opt.is_some() {
+            self.auto_trim();
+        }
+        self.debug_validate();
         elem_opt
     }
     /// Move the element at the index to the end.
@@ -573,6 +1433,54 @@ impl<T> IndexList<T> {
             // insert it as last
             self.linkin_last(index);
         }
+        self.debug_validate();
+    }
+    /// Move the element at the index to the beginning.
+    /// The index remains the same.
+    pub fn move_to_first(&mut self, index: ListIndex) {
+        if self.is_index_used(index) {
+            self.linkout_used(index);
+            self.linkin_first(index);
+        }
+        self.debug_validate();
+    }
+    /// Move the element at the index to just before the element at `that`.
+    /// The index remains the same.
+    ///
+    /// Nothing happens unless both indexes are used and different.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C"]);
+    /// list.move_before(list.last_index(), list.first_index());
+    /// # assert_eq!(list.to_string(), "[C >< A >< B]");
+    /// ```
+    pub fn move_before(&mut self, index: ListIndex, that: ListIndex) {
+        if index != that && self.is_index_used(index) && self.is_index_used(that) {
+            self.linkout_used(index);
+            self.linkin_this_before_that(index, that);
+        }
+        self.debug_validate();
+    }
+    /// Move the element at the index to just after the element at `that`.
+    /// The index remains the same.
+    ///
+    /// Nothing happens unless both indexes are used and different.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C"]);
+    /// list.move_after(list.first_index(), list.last_index());
+    /// # assert_eq!(list.to_string(), "[B >< C >< A]");
+    /// ```
+    pub fn move_after(&mut self, index: ListIndex, that: ListIndex) {
+        if index != that && self.is_index_used(index) && self.is_index_used(that) {
+            self.linkout_used(index);
+            self.linkin_this_after_that(index, that);
+        }
+        self.debug_validate();
     }
     /// Create a new iterator over all the elements.
     ///
@@ -584,13 +1492,252 @@ impl<T> IndexList<T> {
     /// assert_eq!(total, 720);
     /// ```
     #[inline]
-    pub fn iter(&self) -> ListIter<T> {
+    pub fn iter(&self) -> ListIter<'_, T> {
         ListIter {
             list: self,
             next: self.first_index(),
             prev: self.last_index(),
+            remaining: Some(self.len()),
+        }
+    }
+    /// Create a new iterator over the indexes of all the elements.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{IndexList, ListIndex};
+    /// # let mut list = IndexList::from(&mut vec![120, 240, 360]);
+    /// let indexes: Vec<ListIndex> = list.indexes().collect();
+    /// assert_eq!(indexes[0], list.first_index());
+    /// # assert_eq!(indexes[2], list.last_index());
+    /// ```
+    #[inline]
+    pub fn indexes(&self) -> ListIndexes<'_, T> {
+        ListIndexes {
+            list: self,
+            next: self.first_index(),
+            prev: self.last_index(),
+            remaining: self.len(),
+        }
+    }
+    /// Create a new iterator over all the elements, together with their
+    /// indexes.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![120, 240, 360]);
+    /// for (index, elem) in list.iter_indexed() {
+    ///     assert_eq!(list.get(index), Some(elem));
+    /// }
+    /// ```
+    #[inline]
+    pub fn iter_indexed(&self) -> ListIndexedIter<'_, T> {
+        ListIndexedIter(self.indexes())
+    }
+    /// Create a new iterator over mutable references to all the elements,
+    /// together with their indexes.
+    ///
+    /// Without unsafe code the references can only be handed out in list
+    /// order by collecting them first, so creating the iterator allocates a
+    /// vector with one entry per index and another with one per element, and
+    /// takes time in proportion to the capacity. Use `iter_slots_mut` or
+    /// `for_each_unordered_mut` when the order does not matter, as they do
+    /// not allocate.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![120, 240, 360]);
+    /// for (_index, elem) in list.iter_indexed_mut() {
+    ///     *elem /= 120;
+    /// }
+    /// # assert_eq!(list.to_string(), "[1 >< 2 >< 3]");
+    /// ```
+    pub fn iter_indexed_mut(&mut self) -> ListIndexedIterMut<'_, T> {
+        let mut slots: Vec<Option<&mut T>> = self.elems.iter_mut().map(Option::as_mut).collect();
+        let mut items = Vec::with_capacity(self.size);
+        let mut index = self.used.head;
+        while let Some(at) = index.get() {
+            if let Some(elem) = slots[at].take() {
+                items.push((index, elem));
+            }
+            index = self.nodes[at].next;
+        }
+        ListIndexedIterMut { items: items.into_iter() }
+    }
+    /// Create a new iterator over mutable references to all the elements.
+    ///
+    /// This is built on `iter_indexed_mut`, so creating the iterator
+    /// allocates two vectors and takes time in proportion to the capacity.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![120, 240, 360]);
+    /// list.iter_mut().for_each(|elem| *elem += 1);
+    /// # assert_eq!(list.to_string(), "[121 >< 241 >< 361]");
+    /// ```
+    #[inline]
+    pub fn iter_mut(&mut self) -> ListIterMut<'_, T> {
+        ListIterMut(self.iter_indexed_mut())
+    }
+    /// Create a new iterator over all the elements and their indexes, in the
+    /// order they are stored rather than the list order.
+    ///
+    /// This is faster than walking the list when the order does not matter.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![120, 240, 360]);
+    /// list.move_to_first(list.last_index());
+    /// let slots: Vec<u64> = list.iter_slots().map(|(_, elem)| *elem).collect();
+    /// assert_eq!(slots, vec![120, 240, 360]);
+    /// ```
+    #[inline]
+    pub fn iter_slots(&self) -> ListSlotIter<'_, T> {
+        ListSlotIter {
+            slots: self.elems.iter().enumerate(),
+            remaining: self.len(),
+        }
+    }
+    /// Create a new iterator over mutable references to all the elements and
+    /// their indexes, in the order they are stored rather than the list order.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![120, 240, 360]);
+    /// for (_index, elem) in list.iter_slots_mut() {
+    ///     *elem /= 120;
+    /// }
+    /// # assert_eq!(list.to_string(), "[1 >< 2 >< 3]");
+    /// ```
+    #[inline]
+    pub fn iter_slots_mut(&mut self) -> ListSlotIterMut<'_, T> {
+        ListSlotIterMut {
+            remaining: self.len(),
+            slots: self.elems.iter_mut().enumerate(),
         }
     }
+    /// Call the closure for every element, in the order they are stored
+    /// rather than the list order.
+    ///
+    /// This is a plain loop over the element vector, which the compiler may be
+    /// able to vectorize.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![120, 240, 360]);
+    /// let mut total = 0;
+    /// list.for_each_unordered(|elem| total += elem);
+    /// assert_eq!(total, 720);
+    /// ```
+    #[inline]
+    pub fn for_each_unordered<F>(&self, f: F)
+    where
+        F: FnMut(&T),
+    {
+        self.elems.iter().flatten().for_each(f);
+    }
+    /// Call the closure with a mutable reference to every element, in the
+    /// order they are stored rather than the list order.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![120, 240, 360]);
+    /// list.for_each_unordered_mut(|elem| *elem /= 120);
+    /// # assert_eq!(list.to_string(), "[1 >< 2 >< 3]");
+    /// ```
+    #[inline]
+    pub fn for_each_unordered_mut<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut T),
+    {
+        self.elems.iter_mut().flatten().for_each(f);
+    }
+    /// Create a new iterator from the element at the index to the end.
+    ///
+    /// The iterator is empty if the index is not in use. It is created in
+    /// constant time, so its size hint is not exact.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C", "D"]);
+    /// let index = list.next_index(list.first_index());
+    /// let items: Vec<&str> = list.iter_from(index).copied().collect();
+    /// assert_eq!(items, vec!["B", "C", "D"]);
+    /// ```
+    pub fn iter_from(&self, index: ListIndex) -> ListIter<'_, T> {
+        if !self.is_index_used(index) {
+            return ListIter { list: self, next: index, prev: index, remaining: Some(0) };
+        }
+        ListIter { list: self, next: index, prev: self.last_index(), remaining: None }
+    }
+    /// Create a new iterator from the element at the index backwards to the
+    /// beginning.
+    ///
+    /// The iterator is empty if the index is not in use. It is created in
+    /// constant time, so its size hint is not exact.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C", "D"]);
+    /// let index = list.prev_index(list.last_index());
+    /// let items: Vec<&str> = list.iter_back_from(index).copied().collect();
+    /// assert_eq!(items, vec!["C", "B", "A"]);
+    /// ```
+    pub fn iter_back_from(&self, index: ListIndex) -> Rev<ListIter<'_, T>> {
+        if !self.is_index_used(index) {
+            return ListIter { list: self, next: index, prev: index, remaining: Some(0) }.rev();
+        }
+        ListIter { list: self, next: self.first_index(), prev: index, remaining: None }.rev()
+    }
+    /// Create a new iterator over the elements from index `from` through
+    /// index `to`, which can also be walked backwards from `to`.
+    ///
+    /// The iterator is empty unless both indexes are in use and `to` is found
+    /// by walking forward from `from`, which takes time in proportion to the
+    /// distance between them.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec!["A", "B", "C", "D"]);
+    /// let from = list.next_index(list.first_index());
+    /// let to = list.next_index(from);
+    /// assert_eq!(list.range(from, to).collect::<Vec<_>>(), vec![&"B", &"C"]);
+    /// assert_eq!(list.range(from, to).rev().collect::<Vec<_>>(), vec![&"C", &"B"]);
+    /// assert_eq!(list.range(to, from).count(), 0);
+    /// ```
+    pub fn range(&self, from: ListIndex, to: ListIndex) -> ListIter<'_, T> {
+        let remaining = self.count_from_to(from, to).unwrap_or(0);
+        ListIter { list: self, next: from, prev: to, remaining: Some(remaining) }
+    }
+    /// Create a new iterator over mutable references to the elements from
+    /// index `from` through index `to`, which can also be walked backwards.
+    ///
+    /// The iterator is empty unless both indexes are in use and `to` is found
+    /// by walking forward from `from`. Creating the iterator allocates
+    /// vectors with one entry per element in the range, and takes time in
+    /// proportion to `n log n`, for the `n` elements in the range.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexList;
+    /// # let mut list = IndexList::from(&mut vec![1, 2, 3, 4]);
+    /// let from = list.next_index(list.first_index());
+    /// let to = list.prev_index(list.last_index());
+    /// list.range_mut(from, to).for_each(|elem| *elem *= 10);
+    /// assert_eq!(list.to_string(), "[1 >< 20 >< 30 >< 4]");
+    /// ```
+    pub fn range_mut(&mut self, from: ListIndex, to: ListIndex) -> ListIterMut<'_, T> {
+        ListIterMut(self.indexed_range_mut(from, to))
+    }
     /// Create a draining iterator over all the elements.
     ///
     /// This iterator will remove the elements as it is iterating over them.
@@ -604,7 +1751,7 @@ impl<T> IndexList<T> {
     /// assert_eq!(items, vec!["A", "B", "C"]);
     /// ```
     #[inline]
-    pub fn drain_iter(&mut self) -> ListDrainIter<T> {
+    pub fn drain_iter(&mut self) -> ListDrainIter<'_, T> {
         ListDrainIter::new(self)
     }
     /// Create a vector for all elements.
@@ -656,7 +1803,7 @@ impl<T> IndexList<T> {
     pub fn trim_safe(&mut self) {
         let removed: Vec<usize> = (self.len()..self.capacity())
             .rev()
-            .take_while(|&i| self.is_free(i))
+            .take_while(|&i| self.is_free(i) && !self.is_reserved(i))
             .collect();
         removed.iter().for_each(|&i| {
             self.linkout_free(ListIndex::from(i));
@@ -665,14 +1812,19 @@ impl<T> IndexList<T> {
             let left = self.capacity() - removed.len();
             self.nodes.truncate(left);
             self.elems.truncate(left);
+            if let Some(policy) = self.policy.as_mut() {
+                policy.lowest_free.retain(|&Reverse(at)| at < left);
+            }
         }
+        self.debug_validate();
     }
     /// Remove all unused elements by swapping indexes and then truncating.
     ///
     /// This will reduce the capacity of the list, but only if there are any
     /// unused elements. Length and capacity will be equal after the call.
     ///
-    /// *NOTE* that this call may invalidate some indexes.
+    /// *NOTE* that this call may invalidate some indexes. Any reserved indexes
+    /// are released before trimming.
     ///
     /// While it is possible to tell if an index has become invalid, because
     /// only indexes at or above the new capacity limit has been moved, it is
@@ -688,6 +1840,12 @@ impl<T> IndexList<T> {
     /// assert_eq!(list.len(), list.capacity());
     /// ```
     pub fn trim_swap(&mut self) {
+        let reserved: Vec<usize> = (0..self.capacity())
+            .filter(|&i| self.is_reserved(i))
+            .collect();
+        reserved.iter().for_each(|&i| {
+            self.linkin_free(ListIndex::from(i));
+        });
         let need = self.size;
         // destination is all free node indexes below the needed limit
         let dst: Vec<usize> = self.elems[..need]
@@ -704,12 +1862,25 @@ impl<T> IndexList<T> {
             .map(|(n, _e)| n + need)
             .collect();
         debug_assert_eq!(dst.len(), src.len());
+        let mut relocate = self.policy.as_mut().and_then(|policy| policy.relocate.take());
         src.iter()
             .zip(dst.iter())
-            .for_each(|(s, d)| self.replace_dest_with_source(*s, *d));
+            .for_each(|(s, d)| {
+                self.replace_dest_with_source(*s, *d);
+                if let Some(relocator) = relocate.as_mut() {
+                    relocator.relocated(ListIndex::from(*s), ListIndex::from(*d));
+                }
+            });
+        if let Some(policy) = self.policy.as_mut() {
+            policy.relocate = relocate;
+        }
         self.free.new_both(ListIndex::new());
+        if let Some(policy) = self.policy.as_mut() {
+            policy.lowest_free.clear();
+        }
         self.elems.truncate(need);
         self.nodes.truncate(need);
+        self.debug_validate();
     }
     /// Add the elements of the other list at the end.
     ///
@@ -775,12 +1946,110 @@ impl<T> IndexList<T> {
     /// ```
     pub fn split(&mut self, index: ListIndex) -> IndexList<T> {
         let mut list = IndexList::<T>::new();
+        // trim when done, as compacting could move the index
         while self.is_index_used(index) {
-            list.insert_first(self.remove_last().unwrap());
+            list.insert_first(self.remove_node(self.last_index()).unwrap());
         }
+        self.auto_trim();
+        self.debug_validate();
         list
     }
 
+    // count the elements from one index through another, if it is reached
+    fn count_from_to(&self, from: ListIndex, to: ListIndex) -> Option<usize> {
+        if !self.is_index_used(from) || !self.is_index_used(to) {
+            return None;
+        }
+        let mut count = 1;
+        let mut index = from;
+        while index != to {
+            index = self.nodes[index.get()?].next;
+            count += 1;
+        }
+        Some(count)
+    }
+    // borrow the elements from one index through another, by splitting off
+    // their slots in storage order
+    fn indexed_range_mut(&mut self, from: ListIndex, to: ListIndex) -> ListIndexedIterMut<'_, T> {
+        let mut range = Vec::new();
+        let mut index = if self.is_index_used(to) { from } else { ListIndex::new() };
+        while let Some(at) = index.get().filter(|_| self.is_index_used(index)) {
+            range.push((at, index));
+            if index == to {
+                break;
+            }
+            index = self.nodes[at].next;
+        }
+        if index != to {
+            range.clear();
+        }
+        let mut order: Vec<usize> = (0..range.len()).collect();
+        order.sort_unstable_by_key(|&pos| range[pos].0);
+        let mut elems: Vec<Option<&mut T>> = (0..range.len()).map(|_| None).collect();
+        let (mut rest, mut base) = (&mut self.elems[..], 0);
+        for pos in order {
+            let at = range[pos].0;
+            if let Some((elem, tail)) = mem::take(&mut rest)[at - base..].split_first_mut() {
+                elems[pos] = elem.as_mut();
+                rest = tail;
+                base = at + 1;
+            }
+        }
+        let items: Vec<(ListIndex, &mut T)> = range.iter().zip(elems)
+            .filter_map(|(&(_, index), elem)| Some((index, elem?)))
+            .collect();
+        ListIndexedIterMut { items: items.into_iter() }
+    }
+    // walk a chain, marking its slots as seen, and return its length
+    fn validate_chain(&self, chain: ListChain, ends: &ListEnds, seen: &mut [Option<ListChain>])
+        -> Result<usize, CorruptionReport>
+    {
+        let mut count = 0;
+        let mut last: Option<usize> = None;
+        let mut index = ends.head;
+        while let Some(at) = index.get() {
+            if at >= self.capacity() {
+                let from = ListIndex::from(last.unwrap_or(at));
+                return Err(CorruptionReport::OutOfBounds { chain, index: from, target: index });
+            }
+            match seen[at] {
+                Some(other) if other == chain => {
+                    return Err(CorruptionReport::Cycle { chain, index });
+                }
+                Some(_) => return Err(CorruptionReport::OnBothChains { index }),
+                None => seen[at] = Some(chain),
+            }
+            if (chain == ListChain::Used) != self.is_used(at) {
+                return Err(CorruptionReport::WrongData { chain, index });
+            }
+            match last {
+                None if self.nodes[at].prev.is_some() => {
+                    return Err(CorruptionReport::HeadHasPrev { chain, index });
+                }
+                Some(prev) if self.nodes[at].prev != ListIndex::from(prev) => {
+                    let prev = ListIndex::from(prev);
+                    return Err(CorruptionReport::BrokenLink { chain, index: prev, next: index });
+                }
+                _ => (),
+            }
+            count += 1;
+            last = Some(at);
+            index = self.nodes[at].next;
+        }
+        if ends.tail.get() != last {
+            let last = ListIndex::from(last);
+            return Err(CorruptionReport::TailMismatch { chain, tail: ends.tail, last });
+        }
+        Ok(count)
+    }
+    // panic on any corruption, if enabled by the `validate` feature
+    #[inline]
+    fn debug_validate(&self) {
+        #[cfg(all(feature = "validate", debug_assertions))]
+        if let Err(report) = self.validate() {
+            panic!("corrupted list: {}", report);
+        }
+    }
     #[inline]
     fn is_used(&self, at: usize) -> bool {
         self.elems[at].is_some()
@@ -788,9 +2057,11 @@ impl<T> IndexList<T> {
     fn is_free(&self, at: usize) -> bool {
         self.elems[at].is_none()
     }
-    #[inline]
-    fn get_mut_indexnode(&mut self, at: usize) -> &mut ListNode {
-        &mut self.nodes[at]
+    // a reserved slot has no data and is linked into neither chain
+    fn is_reserved(&self, at: usize) -> bool {
+        self.is_free(at)
+            && self.nodes[at].prev.is_none()
+            && self.free.head != ListIndex::from(at)
     }
     #[inline]
     fn get_indexnode(&self, at: usize) -> &ListNode {
@@ -801,52 +2072,87 @@ impl<T> IndexList<T> {
         self.elems.swap(here, there);
     }
     #[inline]
-    fn set_prev(&mut self, index: ListIndex, new_prev: ListIndex) -> ListIndex {
-        if let Some(at) = index.get() {
-            self.get_mut_indexnode(at).new_prev(new_prev)
-        } else {
-            index
+    fn insert_elem_at_index(&mut self, this: ListIndex, elem: Option<T>) {
+        if let Some(at) = this.get() {
+            self.elems[at] = elem;
+            self.size += 1;
         }
     }
     #[inline]
-    fn set_next(&mut self, index: ListIndex, new_next: ListIndex) -> ListIndex {
-        if let Some(at) = index.get() {
-            self.get_mut_indexnode(at).new_next(new_next)
-        } else {
-            index
+    fn remove_elem_at_index(&mut self, this: ListIndex) -> Option<T> {
+        let elem = self.elems.get_mut(this.get()?)?.take();
+        if elem.is_some() {
+            self.size -= 1;
         }
+        elem
     }
-    #[inline]
-    fn linkin_tail(&mut self, prev: ListIndex, this: ListIndex, next: ListIndex) {
-        if next.is_none() {
-            let old_tail = self.used.new_tail(this);
-            debug_assert_eq!(old_tail, prev);
+    fn remove_node(&mut self, index: ListIndex) -> Option<T> {
+        let elem_opt = self.remove_elem_at_index(index);
+        if elem_opt.is_some() {
+            self.linkout_used(index);
+            self.linkin_free(index);
         }
+        elem_opt
     }
+    // the policy state, allocated on first use
     #[inline]
-    fn linkin_head(&mut self, prev: ListIndex, this: ListIndex, next: ListIndex) {
-        if prev.is_none() {
-            let old_head = self.used.new_head(this);
-            debug_assert_eq!(old_head, next);
-        }
+    fn policy_mut(&mut self) -> &mut PolicyState {
+        self.policy.get_or_insert_with(Default::default)
     }
     #[inline]
-    fn insert_elem_at_index(&mut self, this: ListIndex, elem: Option<T>) {
-        if let Some(at) = this.get() {
-            self.elems[at] = elem;
-            self.size += 1;
-        }
+    fn heap_bytes(&self) -> usize {
+        self.elems.capacity() * mem::size_of::<Option<T>>()
+            + self.nodes.capacity() * mem::size_of::<ListNode>()
     }
     #[inline]
-    fn remove_elem_at_index(&mut self, this: ListIndex) -> Option<T> {
-        this.get()
-            .and_then(|at| {
-                self.size -= 1;
-                self.elems[at].take()
-            })
+    fn is_below_load(&self, min_load: f32) -> bool {
+        (self.len() as f32) < min_load * self.capacity() as f32
+    }
+    fn auto_trim(&mut self) {
+        let trim = self.trim_policy();
+        let min_load = match trim {
+            TrimPolicy::Manual => return,
+            TrimPolicy::Safe { min_load } | TrimPolicy::Compact { min_load } => min_load,
+        };
+        if !self.is_below_load(min_load) {
+            return;
+        }
+        let (capacity, bytes) = (self.capacity(), self.heap_bytes());
+        self.trim_safe();
+        if matches!(trim, TrimPolicy::Compact { .. }) && self.is_below_load(min_load) {
+            self.trim_swap();
+        }
+        if self.capacity() < capacity {
+            // release the memory once it is mostly unused
+            if self.elems.capacity() > 2 * self.capacity() {
+                self.elems.shrink_to_fit();
+                self.nodes.shrink_to_fit();
+            }
+            let reclaimed = bytes - self.heap_bytes();
+            let stats = &mut self.policy_mut().trim_stats;
+            stats.runs += 1;
+            stats.bytes_reclaimed += reclaimed;
+        }
+    }
+    fn next_free(&mut self) -> ListIndex {
+        match self.reuse_policy() {
+            ReusePolicy::Fifo => self.free.head,
+            ReusePolicy::Lifo => self.free.tail,
+            ReusePolicy::LowestFirst => {
+                // skip entries that are no longer on the free chain
+                while let Some(Reverse(at)) =
+                    self.policy.as_mut().and_then(|policy| policy.lowest_free.pop())
+                {
+                    if at < self.capacity() && self.is_free(at) && !self.is_reserved(at) {
+                        return ListIndex::from(at);
+                    }
+                }
+                ListIndex::new()
+            }
+        }
     }
     fn new_node(&mut self, elem: Option<T>) -> ListIndex {
-        let reuse = self.free.head;
+        let reuse = self.next_free();
         if reuse.is_some() {
             self.insert_elem_at_index(reuse, elem);
             self.linkout_free(reuse);
@@ -858,103 +2164,80 @@ impl<T> IndexList<T> {
         self.size += 1;
         ListIndex::from(pos)
     }
+    fn new_node_with<F>(&mut self, f: F) -> ListIndex
+    where
+        F: FnOnce(ListIndex) -> T,
+    {
+        let this = self.new_node(None);
+        let guard = NewNodeGuard { list: self, index: this };
+        let elem = f(this);
+        if let Some(at) = this.get() {
+            guard.list.elems[at] = Some(elem);
+        }
+        this
+    }
+    // the number of new slots needed to hold `additional` more elements
+    fn extra_slots(&self, additional: usize) -> usize {
+        let spare = self.capacity() - self.size;
+        additional.saturating_sub(spare)
+    }
+    // link in a run of new elements after prev, or else before next
+    fn insert_run<I, F>(&mut self, prev: ListIndex, next: ListIndex, iter: I, mut f: F)
+    where
+        I: IntoIterator<Item = T>,
+        F: FnMut(ListIndex),
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        let mut prev = prev;
+        for elem in iter {
+            let this = self.new_node(Some(elem));
+            if prev.is_some() {
+                self.linkin_this_after_that(this, prev);
+            } else if next.is_some() {
+                self.linkin_this_before_that(this, next);
+            } else {
+                self.linkin_last(this);
+            }
+            f(this);
+            prev = this;
+        }
+        self.debug_validate();
+    }
     fn linkin_free(&mut self, this: ListIndex) {
         debug_assert!(!self.is_index_used(this));
-        let prev = self.free.tail;
-        self.set_next(prev, this);
-        self.set_prev(this, prev);
-        if self.free.is_empty() {
-            self.free.new_both(this);
-        } else {
-            let old_tail = self.free.new_tail(this);
-            debug_assert_eq!(old_tail, prev);
+        if let (Some(policy), Some(at)) = (self.policy.as_mut(), this.get()) {
+            if policy.reuse == ReusePolicy::LowestFirst {
+                policy.lowest_free.push(Reverse(at));
+            }
         }
+        self.free.link_last(&mut self.nodes, this);
     }
     fn linkin_first(&mut self, this: ListIndex) {
         debug_assert!(self.is_index_used(this));
-        let next = self.used.head;
-        self.set_prev(next, this);
-        self.set_next(this, next);
-        if self.used.is_empty() {
-            self.used.new_both(this);
-        } else {
-            let old_head = self.used.new_head(this);
-            debug_assert_eq!(old_head, next);
-        }
+        self.used.link_first(&mut self.nodes, this);
     }
     fn linkin_last(&mut self, this: ListIndex) {
         debug_assert!(self.is_index_used(this));
-        let prev = self.used.tail;
-        self.set_next(prev, this);
-        self.set_prev(this, prev);
-        if self.used.is_empty() {
-            self.used.new_both(this);
-        } else {
-            let old_tail = self.used.new_tail(this);
-            debug_assert_eq!(old_tail, prev);
-        }
+        self.used.link_last(&mut self.nodes, this);
     }
     // prev? >< that => prev? >< this >< that
     fn linkin_this_before_that(&mut self, this: ListIndex, that: ListIndex) {
         debug_assert!(self.is_index_used(this));
         debug_assert!(self.is_index_used(that));
-        let prev = self.set_prev(that, this);
-        let old_next = self.set_next(prev, this);
-        if old_next.is_some() {
-            debug_assert_eq!(old_next, that);
-        }
-        self.set_prev(this, prev);
-        self.set_next(this, that);
-        self.linkin_head(prev, this, that);
+        self.used.link_before(&mut self.nodes, this, that);
     }
     // that >< next? => that >< this >< next?
     fn linkin_this_after_that(&mut self, this: ListIndex, that: ListIndex) {
         debug_assert!(self.is_index_used(this));
         debug_assert!(self.is_index_used(that));
-        let next = self.set_next(that, this);
-        let old_prev = self.set_prev(next, this);
-        if old_prev.is_some() {
-            debug_assert_eq!(old_prev, that);
-        }
-        self.set_prev(this, that);
-        self.set_next(this, next);
-        self.linkin_tail(that, this, next);
-    }
-    // prev >< this >< next => prev >< next
-    fn linkout_node(&mut self, this: ListIndex) -> (ListIndex, ListIndex) {
-        let next = self.set_next(this, ListIndex::new());
-        let prev = self.set_prev(this, ListIndex::new());
-        let old_prev = self.set_prev(next, prev);
-        if old_prev.is_some() {
-            debug_assert_eq!(old_prev, this);
-        }
-        let old_next = self.set_next(prev, next);
-        if old_next.is_some() {
-            debug_assert_eq!(old_next, this);
-        }
-        (prev, next)
+        self.used.link_after(&mut self.nodes, this, that);
     }
     fn linkout_used(&mut self, this: ListIndex) {
-        let (prev, next) = self.linkout_node(this);
-        if next.is_none() {
-            let old_tail = self.used.new_tail(prev);
-            debug_assert_eq!(old_tail, this);
-        }
-        if prev.is_none() {
-            let old_head = self.used.new_head(next);
-            debug_assert_eq!(old_head, this);
-        }
+        self.used.unlink(&mut self.nodes, this);
     }
     fn linkout_free(&mut self, this: ListIndex) {
-        let (prev, next) = self.linkout_node(this);
-        if next.is_none() {
-            let old_tail = self.free.new_tail(prev);
-            debug_assert_eq!(old_tail, this);
-        }
-        if prev.is_none() {
-            let old_head = self.free.new_head(next);
-            debug_assert_eq!(old_head, this);
-        }
+        self.free.unlink(&mut self.nodes, this);
     }
     fn replace_dest_with_source(&mut self, src: usize, dst: usize) {
         debug_assert!(self.is_free(dst));
@@ -981,8 +2264,33 @@ where
     T: fmt::Display,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let elems: Vec<String> = self.iter().map(|x| format!("{}", x)).collect();
-        write!(f, "[{}]", elems.join(" >< "))
+        self.display().fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for IndexList<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<ListIndex> for IndexList<T> {
+    type Output = T;
+    /// Panics if the index is not in use.
+    fn index(&self, index: ListIndex) -> &Self::Output {
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {} is not in use", index))
+    }
+}
+
+impl<T> IndexMut<ListIndex> for IndexList<T> {
+    /// Panics if the index is not in use.
+    fn index_mut(&mut self, index: ListIndex) -> &mut Self::Output {
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index {} is not in use", index))
     }
 }
 
@@ -997,17 +2305,31 @@ impl<T> From<T> for IndexList<T> {
 impl<T> FromIterator<T> for IndexList<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut list = IndexList::new();
-        for elem in iter {
-            list.insert_last(elem);
-        }
+        list.extend(iter);
         list
     }
 }
 
 impl<T> Extend<T> for IndexList<T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        for elem in iter {
-            self.insert_last(elem);
+        self.insert_run(self.last_index(), ListIndex::new(), iter, |_| ());
+    }
+}
+
+// returns a new node to the free chain if its element was never stored,
+// which only happens when the closure making the element panics
+struct NewNodeGuard<'a, T> {
+    list: &'a mut IndexList<T>,
+    index: ListIndex,
+}
+
+impl<T> Drop for NewNodeGuard<'_, T> {
+    fn drop(&mut self) {
+        if let Some(at) = self.index.get() {
+            if self.list.elems[at].is_none() {
+                self.list.size -= 1;
+                self.list.linkin_free(self.index);
+            }
         }
     }
 }
@@ -1022,6 +2344,81 @@ mod tests {
         assert_eq!(size_of::<ListIndex>(), 4);
         assert_eq!(size_of::<ListNode>(), 8);
         assert_eq!(size_of::<ListEnds>(), 8);
-        assert_eq!(size_of::<IndexList<u32>>(), 72);
+        assert_eq!(size_of::<IndexList<u32>>(), 80);
+        // the default policies need no state
+        let mut list = IndexList::<u32>::new();
+        list.set_reuse_policy(ReusePolicy::Fifo);
+        list.set_trim_policy(TrimPolicy::Safe { min_load: 0.0 });
+        assert!(list.policy.is_none());
+    }
+    fn assert_send_sync<S: Send + Sync>() {}
+    fn assert_send<S: Send>() {}
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<IndexList<u32>>();
+        assert_send_sync::<IndexListMap<u32, u32>>();
+        assert_send_sync::<IndexListSet<u32>>();
+        assert_send_sync::<RankedIndexList<u32>>();
+        assert_send_sync::<LabeledIndexList<u32>>();
+        assert_send_sync::<TimerWheel<u32>>();
+        // the eviction callbacks are only Send
+        assert_send::<LruCache<u32, u32>>();
+        assert_send::<LfuCache<u32, u32>>();
+    }
+    #[test]
+    fn test_layout_cut_off() {
+        let mut list: IndexList<u32> = (0..4).collect();
+        list.remove(ListIndex::from(3u32));
+        list.nodes[2].next = ListIndex::from(0u32);
+        let slots = list.layout();
+        assert!(slots[2].cut_off);
+        assert_eq!(slots[2].to_string(), "3: used data 1<>2 cut off");
+        assert_eq!(slots.iter().filter(|slot| slot.cut_off).count(), 1);
+        assert!(list.to_dot().contains("n3 -> used_cut_off [color=red];"));
+        list.free.new_both(ListIndex::from(9u32));
+        assert_eq!(list.layout()[3].chain, None);
+    }
+    #[test]
+    fn test_validate_corruption() {
+        let fresh = || {
+            let mut list: IndexList<u32> = (0..6).collect();
+            list.remove(ListIndex::from(1u32));
+            list.remove(ListIndex::from(4u32));
+            assert_eq!(list.validate(), Ok(()));
+            list
+        };
+        let mut list = fresh();
+        list.size = 5;
+        assert_eq!(list.validate(), Err(CorruptionReport::SizeMismatch { size: 5, counted: 4 }));
+        let mut list = fresh();
+        list.nodes[3].prev = ListIndex::from(0u32);
+        assert_eq!(list.validate(),
+                   Err(CorruptionReport::BrokenLink {
+                       chain: ListChain::Used, index: ListIndex::from(2u32), next: ListIndex::from(3u32) }));
+        let mut list = fresh();
+        list.nodes[5].next = ListIndex::from(2u32);
+        assert_eq!(list.validate(),
+                   Err(CorruptionReport::Cycle { chain: ListChain::Used, index: ListIndex::from(2u32) }));
+        let mut list = fresh();
+        list.nodes[4].next = ListIndex::from(3u32);
+        assert_eq!(list.validate(), Err(CorruptionReport::OnBothChains { index: ListIndex::from(3u32) }));
+        let mut list = fresh();
+        list.elems[4] = Some(4);
+        assert_eq!(list.validate(),
+                   Err(CorruptionReport::WrongData { chain: ListChain::Free, index: ListIndex::from(4u32) }));
+        let mut list = fresh();
+        list.used.tail = ListIndex::from(3u32);
+        assert_eq!(list.validate(), Err(CorruptionReport::TailMismatch {
+            chain: ListChain::Used, tail: ListIndex::from(3u32), last: ListIndex::from(5u32) }));
+        let mut list = fresh();
+        list.free.new_both(ListIndex::new());
+        let report = list.validate().unwrap_err();
+        assert_eq!(report, CorruptionReport::Unlinked { index: ListIndex::from(1u32) });
+        // reported the same way as layout shows it
+        assert_eq!(report.to_string(), format!("index {} is on neither chain", list.layout()[1].index));
+        let mut list = fresh();
+        list.nodes[5].next = ListIndex::from(9u32);
+        assert_eq!(list.validate(), Err(CorruptionReport::OutOfBounds {
+            chain: ListChain::Used, index: ListIndex::from(5u32), target: ListIndex::from(9u32) }));
     }
 }
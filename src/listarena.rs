@@ -0,0 +1,501 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the IndexListArena type
+//!
+//! The arena owns one element vector and one node vector, shared by any
+//! number of lists. Each list is just its `ListEnds` and its length, and each
+//! slot remembers which list it belongs to. All the unused slots are linked
+//! together on a single free chain, so a slot freed by one list can be reused
+//! by any other. Since the links of an element live in the shared node vector,
+//! it can be moved from one list to another by relinking it, and it keeps its
+//! index.
+use std::{default::Default, fmt, iter::FusedIterator};
+use std::ops::{Index, IndexMut};
+use crate::{listends::ListEnds, listindex::ListIndex, listnode::ListNode};
+
+/// A handle to one of the lists in an `IndexListArena`.
+///
+/// The handle of a removed list stays invalid, even when its slot is reused
+/// by a new list, as each handle also carries the generation of the slot.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ListId(usize, u32);
+
+#[derive(Clone, Debug, Default)]
+struct ArenaList {
+    ends: ListEnds,
+    len: usize,
+}
+
+/// Storage shared by many lists, where elements can be moved between the lists
+/// in constant time without changing their index.
+pub struct IndexListArena<T> {
+    elems: Vec<Option<T>>,
+    nodes: Vec<ListNode>,
+    owner: Vec<usize>,
+    free: ListEnds,
+    size: usize,
+    lists: Vec<Option<ArenaList>>,
+    // bumped every time a list is removed, to tell stale handles apart
+    generations: Vec<u32>,
+    unused: Vec<usize>,
+}
+
+impl<T> Default for IndexListArena<T> {
+    fn default() -> Self {
+        IndexListArena {
+            elems: Vec::new(),
+            nodes: Vec::new(),
+            owner: Vec::new(),
+            free: ListEnds::new(),
+            size: 0,
+            lists: Vec::new(),
+            generations: Vec::new(),
+            unused: Vec::new(),
+        }
+    }
+}
+
+impl<T> IndexListArena<T> {
+    /// Creates a new empty arena, without any lists.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::IndexListArena;
+    ///
+    /// let arena = IndexListArena::<u64>::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Creates a new empty arena with room for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexListArena {
+            elems: Vec::with_capacity(capacity),
+            nodes: Vec::with_capacity(capacity),
+            owner: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+    /// Returns the number of slots, used or free, shared by all the lists.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.elems.len()
+    }
+    /// Returns the number of elements in all the lists.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.size
+    }
+    /// Returns true when there are no elements in any list.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+    /// Add a new empty list to the arena and return its handle.
+    ///
+    /// The slots of removed lists are reused, but not their handles.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexListArena;
+    /// let mut arena = IndexListArena::<u64>::new();
+    /// let old = arena.new_list();
+    /// arena.remove_list(old);
+    /// let new = arena.new_list();
+    /// assert_ne!(old, new);
+    /// assert!(!arena.is_list(old));
+    /// ```
+    pub fn new_list(&mut self) -> ListId {
+        let slot = match self.unused.pop() {
+            Some(slot) => {
+                self.lists[slot] = Some(ArenaList::default());
+                slot
+            }
+            None => {
+                self.lists.push(Some(ArenaList::default()));
+                self.generations.push(0);
+                self.lists.len() - 1
+            }
+        };
+        self.id_of(slot)
+    }
+    /// Remove the list and all its elements from the arena.
+    ///
+    /// Returns false if there is no such list.
+    pub fn remove_list(&mut self, id: ListId) -> bool {
+        if !self.clear_list(id) {
+            return false;
+        }
+        self.lists[id.0] = None;
+        self.generations[id.0] = self.generations[id.0].wrapping_add(1);
+        self.unused.push(id.0);
+        true
+    }
+    /// Remove all the elements of the list, keeping the list itself.
+    ///
+    /// Returns false if there is no such list.
+    pub fn clear_list(&mut self, id: ListId) -> bool {
+        if !self.is_list(id) {
+            return false;
+        }
+        while self.remove_first(id).is_some() {}
+        true
+    }
+    /// Returns true if the handle refers to a list in the arena, and not to
+    /// one that has been removed.
+    #[inline]
+    pub fn is_list(&self, id: ListId) -> bool {
+        self.list(id).is_some()
+    }
+    /// Create an iterator over the handles of all the lists in the arena.
+    pub fn list_ids(&self) -> impl Iterator<Item = ListId> + '_ {
+        self.lists.iter().enumerate()
+            .filter(|(_, list)| list.is_some())
+            .map(|(slot, _)| self.id_of(slot))
+    }
+    /// Returns the number of elements in the list, or zero if there is no
+    /// such list.
+    #[inline]
+    pub fn list_len(&self, id: ListId) -> usize {
+        self.list(id).map_or(0, |list| list.len)
+    }
+    /// Returns the list that the element at the index belongs to, or `None`.
+    #[inline]
+    pub fn list_of(&self, index: ListIndex) -> Option<ListId> {
+        self.is_index_used(index).then(|| self.id_of(self.owner[index.get().unwrap()]))
+    }
+    /// Returns true if the index is used by an element in any of the lists.
+    #[inline]
+    pub fn is_index_used(&self, index: ListIndex) -> bool {
+        self.get(index).is_some()
+    }
+    /// Returns the index of the first element of the list, or `None`.
+    #[inline]
+    pub fn first_index(&self, id: ListId) -> ListIndex {
+        self.list(id).map_or_else(ListIndex::new, |list| list.ends.head)
+    }
+    /// Returns the index of the last element of the list, or `None`.
+    #[inline]
+    pub fn last_index(&self, id: ListId) -> ListIndex {
+        self.list(id).map_or_else(ListIndex::new, |list| list.ends.tail)
+    }
+    /// Returns the index of the next element in the same list, or `None`.
+    #[inline]
+    pub fn next_index(&self, index: ListIndex) -> ListIndex {
+        self.used_node(index).map_or_else(ListIndex::new, |node| node.next)
+    }
+    /// Returns the index of the previous element in the same list, or `None`.
+    #[inline]
+    pub fn prev_index(&self, index: ListIndex) -> ListIndex {
+        self.used_node(index).map_or_else(ListIndex::new, |node| node.prev)
+    }
+    /// Get a reference to the element data at the index, or `None`.
+    #[inline]
+    pub fn get(&self, index: ListIndex) -> Option<&T> {
+        self.elems.get(index.get()?)?.as_ref()
+    }
+    /// Get a mutable reference to the element data at the index, or `None`.
+    #[inline]
+    pub fn get_mut(&mut self, index: ListIndex) -> Option<&mut T> {
+        self.elems.get_mut(index.get()?)?.as_mut()
+    }
+    /// Insert a new element at the beginning of the list.
+    ///
+    /// Panics if there is no such list.
+    pub fn insert_first(&mut self, id: ListId, elem: T) -> ListIndex {
+        let this = self.new_node(id, elem);
+        self.linkin_first(this, id);
+        this
+    }
+    /// Insert a new element at the end of the list.
+    ///
+    /// Panics if there is no such list.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexListArena;
+    /// let mut arena = IndexListArena::new();
+    /// let queue = arena.new_list();
+    /// arena.insert_last(queue, "job");
+    /// assert_eq!(arena.list_len(queue), 1);
+    /// ```
+    pub fn insert_last(&mut self, id: ListId, elem: T) -> ListIndex {
+        let this = self.new_node(id, elem);
+        self.linkin_last(this, id);
+        this
+    }
+    /// Insert a new element just before the one at the index, in its list.
+    ///
+    /// Panics if the index is not in use.
+    pub fn insert_before(&mut self, index: ListIndex, elem: T) -> ListIndex {
+        let id = self.owner_of(index);
+        let this = self.new_node(id, elem);
+        self.linkin_before(this, index);
+        this
+    }
+    /// Insert a new element just after the one at the index, in its list.
+    ///
+    /// Panics if the index is not in use.
+    pub fn insert_after(&mut self, index: ListIndex, elem: T) -> ListIndex {
+        let id = self.owner_of(index);
+        let this = self.new_node(id, elem);
+        self.linkin_after(this, index);
+        this
+    }
+    /// Remove the element at the index from its list and return its data.
+    pub fn remove(&mut self, index: ListIndex) -> Option<T> {
+        let elem = self.elems.get_mut(index.get()?)?.take()?;
+        self.linkout(index);
+        self.linkin_free(index);
+        self.size -= 1;
+        Some(elem)
+    }
+    /// Remove the first element of the list and return its data.
+    #[inline]
+    pub fn remove_first(&mut self, id: ListId) -> Option<T> {
+        self.remove(self.first_index(id))
+    }
+    /// Remove the last element of the list and return its data.
+    #[inline]
+    pub fn remove_last(&mut self, id: ListId) -> Option<T> {
+        self.remove(self.last_index(id))
+    }
+    /// Move the element at the index to the beginning of the list, which may
+    /// be a different list than the one it is in now.
+    ///
+    /// The element keeps its index. Nothing happens if the index is not in
+    /// use or there is no such list.
+    pub fn move_to_first(&mut self, index: ListIndex, id: ListId) {
+        if self.is_index_used(index) && self.is_list(id) {
+            self.linkout(index);
+            self.set_owner(index, id);
+            self.linkin_first(index, id);
+        }
+    }
+    /// Move the element at the index to the end of the list, which may be a
+    /// different list than the one it is in now.
+    ///
+    /// The element keeps its index. Nothing happens if the index is not in
+    /// use or there is no such list.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexListArena;
+    /// let mut arena = IndexListArena::new();
+    /// let (todo, done) = (arena.new_list(), arena.new_list());
+    /// let task = arena.insert_last(todo, "write docs");
+    /// arena.move_to_last(task, done);
+    /// assert_eq!(arena.list_of(task), Some(done));
+    /// assert_eq!(arena.list_len(todo), 0);
+    /// assert_eq!(arena.get(task), Some(&"write docs"));
+    /// ```
+    pub fn move_to_last(&mut self, index: ListIndex, id: ListId) {
+        if self.is_index_used(index) && self.is_list(id) {
+            self.linkout(index);
+            self.set_owner(index, id);
+            self.linkin_last(index, id);
+        }
+    }
+    /// Move the element at the index to just before the element at `that`,
+    /// into the list of `that`.
+    ///
+    /// The element keeps its index. Nothing happens if either index is not
+    /// in use.
+    pub fn move_before(&mut self, index: ListIndex, that: ListIndex) {
+        if index != that && self.is_index_used(index) && self.is_index_used(that) {
+            self.linkout(index);
+            self.set_owner(index, self.owner_of(that));
+            self.linkin_before(index, that);
+        }
+    }
+    /// Move the element at the index to just after the element at `that`,
+    /// into the list of `that`.
+    ///
+    /// The element keeps its index. Nothing happens if either index is not
+    /// in use.
+    pub fn move_after(&mut self, index: ListIndex, that: ListIndex) {
+        if index != that && self.is_index_used(index) && self.is_index_used(that) {
+            self.linkout(index);
+            self.set_owner(index, self.owner_of(that));
+            self.linkin_after(index, that);
+        }
+    }
+    /// Create an iterator over the elements of the list, in order.
+    #[inline]
+    pub fn iter(&self, id: ListId) -> ArenaIter<'_, T> {
+        ArenaIter {
+            arena: self,
+            next: self.first_index(id),
+            prev: self.last_index(id),
+            remaining: self.list_len(id),
+        }
+    }
+    /// Remove all the lists and their elements.
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+
+    // the current handle of the list in the slot
+    #[inline]
+    fn id_of(&self, slot: usize) -> ListId {
+        ListId(slot, self.generations[slot])
+    }
+    #[inline]
+    fn is_current(&self, id: ListId) -> bool {
+        self.generations.get(id.0) == Some(&id.1)
+    }
+    #[inline]
+    fn list(&self, id: ListId) -> Option<&ArenaList> {
+        self.lists.get(id.0)?.as_ref().filter(|_| self.is_current(id))
+    }
+    #[inline]
+    fn used_node(&self, index: ListIndex) -> Option<&ListNode> {
+        self.is_index_used(index).then(|| &self.nodes[index.get().unwrap()])
+    }
+    #[inline]
+    fn owner_of(&self, index: ListIndex) -> ListId {
+        self.list_of(index).unwrap_or_else(|| panic!("index {} is not in use", index))
+    }
+    #[inline]
+    fn set_owner(&mut self, index: ListIndex, id: ListId) {
+        if let Some(at) = index.get() {
+            self.owner[at] = id.0;
+        }
+    }
+    // the list and the nodes its chain is linked through, borrowed together
+    #[inline]
+    fn chain_mut(&mut self, id: ListId) -> (&mut ArenaList, &mut [ListNode]) {
+        if !self.is_current(id) {
+            panic!("list {:?} is not in the arena", id);
+        }
+        match self.lists.get_mut(id.0) {
+            Some(Some(list)) => (list, &mut self.nodes),
+            _ => panic!("list {:?} is not in the arena", id),
+        }
+    }
+    fn new_node(&mut self, id: ListId, elem: T) -> ListIndex {
+        assert!(self.is_list(id), "list {:?} is not in the arena", id);
+        self.size += 1;
+        let reuse = self.free.head;
+        if let Some(at) = reuse.get() {
+            self.free.unlink(&mut self.nodes, reuse);
+            self.elems[at] = Some(elem);
+            self.owner[at] = id.0;
+            return reuse;
+        }
+        self.elems.push(Some(elem));
+        self.nodes.push(ListNode::new());
+        self.owner.push(id.0);
+        ListIndex::from(self.elems.len() - 1)
+    }
+    fn linkin_free(&mut self, this: ListIndex) {
+        self.free.link_last(&mut self.nodes, this);
+    }
+    fn linkin_first(&mut self, this: ListIndex, id: ListId) {
+        let (list, nodes) = self.chain_mut(id);
+        list.ends.link_first(nodes, this);
+        list.len += 1;
+    }
+    fn linkin_last(&mut self, this: ListIndex, id: ListId) {
+        let (list, nodes) = self.chain_mut(id);
+        list.ends.link_last(nodes, this);
+        list.len += 1;
+    }
+    // prev? >< that => prev? >< this >< that, in the list of that
+    fn linkin_before(&mut self, this: ListIndex, that: ListIndex) {
+        let (list, nodes) = self.chain_mut(self.owner_of(that));
+        list.ends.link_before(nodes, this, that);
+        list.len += 1;
+    }
+    // that >< next? => that >< this >< next?, in the list of that
+    fn linkin_after(&mut self, this: ListIndex, that: ListIndex) {
+        let (list, nodes) = self.chain_mut(self.owner_of(that));
+        list.ends.link_after(nodes, this, that);
+        list.len += 1;
+    }
+    // prev >< this >< next => prev >< next, in the list it is owned by
+    fn linkout(&mut self, this: ListIndex) {
+        let id = self.id_of(self.owner[this.get().unwrap()]);
+        let (list, nodes) = self.chain_mut(id);
+        list.ends.unlink(nodes, this);
+        list.len -= 1;
+    }
+}
+
+impl<T> Index<ListIndex> for IndexListArena<T> {
+    type Output = T;
+    fn index(&self, index: ListIndex) -> &Self::Output {
+        self.get(index).unwrap_or_else(|| panic!("index {} is not in use", index))
+    }
+}
+
+impl<T> IndexMut<ListIndex> for IndexListArena<T> {
+    fn index_mut(&mut self, index: ListIndex) -> &mut Self::Output {
+        self.get_mut(index).unwrap_or_else(|| panic!("index {} is not in use", index))
+    }
+}
+
+impl<T> fmt::Debug for IndexListArena<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.list_ids().map(|id| (id, DebugList(self, id))))
+            .finish()
+    }
+}
+
+struct DebugList<'a, T>(&'a IndexListArena<T>, ListId);
+
+impl<T> fmt::Debug for DebugList<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.0.iter(self.1)).finish()
+    }
+}
+
+/// An iterator over the elements of one list in an `IndexListArena`.
+pub struct ArenaIter<'a, T> {
+    arena: &'a IndexListArena<T>,
+    next: ListIndex,
+    prev: ListIndex,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for ArenaIter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let this = self.next;
+        self.next = self.arena.next_index(this);
+        self.arena.get(this)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<T> FusedIterator for ArenaIter<'_, T> {}
+impl<T> ExactSizeIterator for ArenaIter<'_, T> {}
+
+impl<T> DoubleEndedIterator for ArenaIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let this = self.prev;
+        self.prev = self.arena.prev_index(this);
+        self.arena.get(this)
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definitions of the types used to report a corrupted list
+use std::{error::Error, fmt};
+use crate::listindex::ListIndex;
+
+/// The two chains of indexes in a list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ListChain {
+    /// The indexes of the elements in the list.
+    Used,
+    /// The unused indexes, waiting to be reused.
+    Free,
+}
+
+impl fmt::Display for ListChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ListChain::Used => write!(f, "used"),
+            ListChain::Free => write!(f, "free"),
+        }
+    }
+}
+
+/// The first inconsistency found when validating a list. The indexes are
+/// the same as those shown by `layout`, `to_dot` and `display`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CorruptionReport {
+    /// The element and link vectors have different lengths.
+    LengthMismatch { elems: usize, nodes: usize },
+    /// A link, or the head of a chain, is outside of the vector.
+    OutOfBounds { chain: ListChain, index: ListIndex, target: ListIndex },
+    /// The head of a chain has a previous index.
+    HeadHasPrev { chain: ListChain, index: ListIndex },
+    /// The next index does not link back to this index.
+    BrokenLink { chain: ListChain, index: ListIndex, next: ListIndex },
+    /// The last index reached is not the tail of the chain.
+    TailMismatch { chain: ListChain, tail: ListIndex, last: ListIndex },
+    /// The chain loops back to an index already visited.
+    Cycle { chain: ListChain, index: ListIndex },
+    /// The index appears on both chains.
+    OnBothChains { index: ListIndex },
+    /// The index has element data on the free chain, or none on the used
+    /// chain.
+    WrongData { chain: ListChain, index: ListIndex },
+    /// The index is on neither chain, but is not a reserved index.
+    Unlinked { index: ListIndex },
+    /// The stored length is not the number of elements on the used chain.
+    SizeMismatch { size: usize, counted: usize },
+}
+
+impl fmt::Display for CorruptionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CorruptionReport::LengthMismatch { elems, nodes } => {
+                write!(f, "{} elements but {} nodes", elems, nodes)
+            }
+            CorruptionReport::OutOfBounds { chain, index, target } => {
+                write!(f, "{} index {} links to index {} out of bounds", chain, index, target)
+            }
+            CorruptionReport::HeadHasPrev { chain, index } => {
+                write!(f, "{} head index {} has a previous index", chain, index)
+            }
+            CorruptionReport::BrokenLink { chain, index, next } => {
+                write!(f, "{} index {} is not linked back from index {}", chain, index, next)
+            }
+            CorruptionReport::TailMismatch { chain, tail, last } => {
+                write!(f, "{} tail is index {} but the chain ends at index {}", chain, tail, last)
+            }
+            CorruptionReport::Cycle { chain, index } => {
+                write!(f, "{} chain loops back to index {}", chain, index)
+            }
+            CorruptionReport::OnBothChains { index } => {
+                write!(f, "index {} is on both chains", index)
+            }
+            CorruptionReport::WrongData { chain, index } => {
+                write!(f, "{} index {} has the wrong data state", chain, index)
+            }
+            CorruptionReport::Unlinked { index } => {
+                write!(f, "index {} is on neither chain", index)
+            }
+            CorruptionReport::SizeMismatch { size, counted } => {
+                write!(f, "size is {} but {} elements are linked", size, counted)
+            }
+        }
+    }
+}
+
+impl Error for CorruptionReport {}
@@ -6,7 +6,7 @@
 //! The defenition of the ListEnds type
 //!
 use std::{default::Default, fmt, mem};
-use crate::listindex::ListIndex;
+use crate::{listindex::ListIndex, listnode::{set_next, set_prev, ListNode}};
 
 #[derive(Clone, Debug, Default)]
 pub struct ListEnds {
@@ -41,6 +41,80 @@ impl ListEnds {
         self.head = both;
         self.tail = both;
     }
+    // link in an unlinked node at the head of the chain
+    pub fn link_first(&mut self, nodes: &mut [ListNode], this: ListIndex) {
+        let next = self.head;
+        set_prev(nodes, next, this);
+        set_next(nodes, this, next);
+        if self.is_empty() {
+            self.new_both(this);
+        } else {
+            let old_head = self.new_head(this);
+            debug_assert_eq!(old_head, next);
+        }
+    }
+    // link in an unlinked node at the tail of the chain
+    pub fn link_last(&mut self, nodes: &mut [ListNode], this: ListIndex) {
+        let prev = self.tail;
+        set_next(nodes, prev, this);
+        set_prev(nodes, this, prev);
+        if self.is_empty() {
+            self.new_both(this);
+        } else {
+            let old_tail = self.new_tail(this);
+            debug_assert_eq!(old_tail, prev);
+        }
+    }
+    // prev? >< that => prev? >< this >< that
+    pub fn link_before(&mut self, nodes: &mut [ListNode], this: ListIndex, that: ListIndex) {
+        let prev = set_prev(nodes, that, this);
+        let old_next = set_next(nodes, prev, this);
+        if old_next.is_some() {
+            debug_assert_eq!(old_next, that);
+        }
+        set_prev(nodes, this, prev);
+        set_next(nodes, this, that);
+        if prev.is_none() {
+            let old_head = self.new_head(this);
+            debug_assert_eq!(old_head, that);
+        }
+    }
+    // that >< next? => that >< this >< next?
+    pub fn link_after(&mut self, nodes: &mut [ListNode], this: ListIndex, that: ListIndex) {
+        let next = set_next(nodes, that, this);
+        let old_prev = set_prev(nodes, next, this);
+        if old_prev.is_some() {
+            debug_assert_eq!(old_prev, that);
+        }
+        set_prev(nodes, this, that);
+        set_next(nodes, this, next);
+        if next.is_none() {
+            let old_tail = self.new_tail(this);
+            debug_assert_eq!(old_tail, that);
+        }
+    }
+    // prev >< this >< next => prev >< next, leaving this unlinked
+    pub fn unlink(&mut self, nodes: &mut [ListNode], this: ListIndex) -> (ListIndex, ListIndex) {
+        let next = set_next(nodes, this, ListIndex::new());
+        let prev = set_prev(nodes, this, ListIndex::new());
+        let old_prev = set_prev(nodes, next, prev);
+        if old_prev.is_some() {
+            debug_assert_eq!(old_prev, this);
+        }
+        let old_next = set_next(nodes, prev, next);
+        if old_next.is_some() {
+            debug_assert_eq!(old_next, this);
+        }
+        if next.is_none() {
+            let old_tail = self.new_tail(prev);
+            debug_assert_eq!(old_tail, this);
+        }
+        if prev.is_none() {
+            let old_head = self.new_head(next);
+            debug_assert_eq!(old_head, this);
+        }
+        (prev, next)
+    }
 }
 
 impl fmt::Display for ListEnds {
@@ -0,0 +1,155 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definitions of the ListEntry types
+use crate::{listindex::ListIndex, IndexList};
+
+/// A view into a single index of the list, which is either occupied by an
+/// element or vacant.
+pub enum ListEntry<'a, T> {
+    Occupied(OccupiedListEntry<'a, T>),
+    Vacant(VacantListEntry<'a, T>),
+}
+
+impl<'a, T> ListEntry<'a, T> {
+    /// Returns the index of this entry.
+    #[inline]
+    pub fn index(&self) -> ListIndex {
+        match self {
+            ListEntry::Occupied(entry) => entry.index(),
+            ListEntry::Vacant(entry) => entry.index(),
+        }
+    }
+    /// Modify the element data if the entry is occupied.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut T)>(mut self, f: F) -> Self {
+        if let ListEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+    /// Returns the element data, after inserting it last if vacant.
+    #[inline]
+    pub fn or_insert_last(self, elem: T) -> &'a mut T {
+        self.or_insert_with_last(|| elem)
+    }
+    /// Returns the element data, after inserting the result of the closure
+    /// last if vacant.
+    pub fn or_insert_with_last<F: FnOnce() -> T>(self, f: F) -> &'a mut T {
+        match self {
+            ListEntry::Occupied(entry) => entry.into_mut(),
+            ListEntry::Vacant(entry) => entry.insert_last(f()).into_mut(),
+        }
+    }
+}
+
+/// An entry for an index that is used by an element in the list.
+pub struct OccupiedListEntry<'a, T> {
+    list: &'a mut IndexList<T>,
+    index: ListIndex,
+}
+
+impl<'a, T> OccupiedListEntry<'a, T> {
+    pub(crate) fn new(list: &'a mut IndexList<T>, index: ListIndex) -> Self {
+        debug_assert!(list.is_index_used(index));
+        OccupiedListEntry { list, index }
+    }
+    /// Returns the index of the element.
+    #[inline]
+    pub fn index(&self) -> ListIndex {
+        self.index
+    }
+    /// Get an immutable reference to the element data.
+    #[inline]
+    pub fn get(&self) -> &T {
+        &self.list[self.index]
+    }
+    /// Get a mutable reference to the element data.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.list[self.index]
+    }
+    /// Convert the entry into a mutable reference to the element data, with
+    /// the lifetime of the list.
+    #[inline]
+    pub fn into_mut(self) -> &'a mut T {
+        &mut self.list[self.index]
+    }
+    /// Replace the element data and return the old data.
+    #[inline]
+    pub fn replace(&mut self, elem: T) -> T {
+        self.list.replace(self.index, elem).unwrap()
+    }
+    /// Remove the element from the list and return its data.
+    #[inline]
+    pub fn remove(self) -> T {
+        self.list.remove(self.index).unwrap()
+    }
+    /// Insert a new element before this one and return its index.
+    #[inline]
+    pub fn insert_before(&mut self, elem: T) -> ListIndex {
+        self.list.insert_before(self.index, elem)
+    }
+    /// Insert a new element after this one and return its index.
+    #[inline]
+    pub fn insert_after(&mut self, elem: T) -> ListIndex {
+        self.list.insert_after(self.index, elem)
+    }
+    /// Move the element to the beginning of the list.
+    #[inline]
+    pub fn move_to_first(&mut self) {
+        self.list.move_to_first(self.index);
+    }
+    /// Move the element to the end of the list.
+    #[inline]
+    pub fn move_to_last(&mut self) {
+        self.list.move_to_last(self.index);
+    }
+    /// Move the element to just before the element at `that`.
+    #[inline]
+    pub fn move_before(&mut self, that: ListIndex) {
+        self.list.move_before(self.index, that);
+    }
+    /// Move the element to just after the element at `that`.
+    #[inline]
+    pub fn move_after(&mut self, that: ListIndex) {
+        self.list.move_after(self.index, that);
+    }
+}
+
+/// An entry for an index that is not used by any element in the list.
+pub struct VacantListEntry<'a, T> {
+    list: &'a mut IndexList<T>,
+    index: ListIndex,
+}
+
+impl<'a, T> VacantListEntry<'a, T> {
+    pub(crate) fn new(list: &'a mut IndexList<T>, index: ListIndex) -> Self {
+        VacantListEntry { list, index }
+    }
+    /// Returns the index that was requested.
+    #[inline]
+    pub fn index(&self) -> ListIndex {
+        self.index
+    }
+    /// Insert a new element at the beginning of the list.
+    ///
+    /// *NOTE* that the new element is likely given a different index than the
+    /// one requested.
+    #[inline]
+    pub fn insert_first(self, elem: T) -> OccupiedListEntry<'a, T> {
+        let index = self.list.insert_first(elem);
+        OccupiedListEntry::new(self.list, index)
+    }
+    /// Insert a new element at the end of the list.
+    ///
+    /// *NOTE* that the new element is likely given a different index than the
+    /// one requested.
+    #[inline]
+    pub fn insert_last(self, elem: T) -> OccupiedListEntry<'a, T> {
+        let index = self.list.insert_last(elem);
+        OccupiedListEntry::new(self.list, index)
+    }
+}
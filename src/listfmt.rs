@@ -0,0 +1,77 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definitions of the formatting adapters for the list
+use std::fmt;
+use crate::IndexList;
+
+/// Formats the internal vectors and chains of a list for debugging, as
+/// returned by `debug_layout`.
+pub struct ListLayout<'a, T>(pub(crate) &'a IndexList<T>);
+
+impl<T> fmt::Debug for ListLayout<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = self.0;
+        f.debug_struct("IndexList")
+            .field("elems", &list.elems)
+            .field("nodes", &list.nodes)
+            .field("used", &list.used)
+            .field("free", &list.free)
+            .field("size", &list.size)
+            .field("reuse", &list.reuse_policy())
+            .field("trim", &list.trim_policy())
+            .finish()
+    }
+}
+
+/// Formats the elements of a list in order, with a configurable separator
+/// and optionally their indexes, as returned by `display`.
+pub struct ListDisplay<'a, T> {
+    list: &'a IndexList<T>,
+    separator: &'a str,
+    indexes: bool,
+}
+
+impl<'a, T> ListDisplay<'a, T> {
+    pub(crate) fn new(list: &'a IndexList<T>) -> Self {
+        ListDisplay { list, separator: " >< ", indexes: false }
+    }
+    /// Set the separator between the elements, which is `" >< "` by default.
+    #[inline]
+    pub fn separator(mut self, separator: &'a str) -> Self {
+        self.separator = separator;
+        self
+    }
+    /// Set whether each element is preceded by its index and a colon.
+    #[inline]
+    pub fn indexes(mut self, indexes: bool) -> Self {
+        self.indexes = indexes;
+        self
+    }
+}
+
+impl<T> fmt::Display for ListDisplay<'_, T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        let mut index = self.list.first_index();
+        while let Some(elem) = self.list.get(index) {
+            if index != self.list.first_index() {
+                write!(f, "{}", self.separator)?;
+            }
+            if self.indexes {
+                write!(f, "{}:", index)?;
+            }
+            elem.fmt(f)?;
+            index = self.list.next_index(index);
+        }
+        write!(f, "]")
+    }
+}
@@ -3,38 +3,182 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
-//! The defintions of the ListIter type
-use std::iter::{DoubleEndedIterator, FusedIterator};
+//! The defintions of the ListIter types
+use std::iter::{DoubleEndedIterator, ExactSizeIterator, FusedIterator};
+use std::vec;
 use crate::{listindex::ListIndex, IndexList};
 
-/// A double-ended iterator over all the elements in the list. It is fused and
-/// can be reversed.
+/// A double-ended iterator over the elements in the list, or a part of it.
+/// It is fused and can be reversed.
+///
+/// The iterator ends where its two ends meet. Its size hint is exact when it
+/// covers the whole list or a range, but not when it was started from an
+/// index with `iter_from` or `iter_back_from`.
 pub struct ListIter<'a, T> {
     pub(crate) list: &'a IndexList<T>,
     pub(crate) next: ListIndex,
     pub(crate) prev: ListIndex,
+    // the number of elements left, when it is known
+    pub(crate) remaining: Option<usize>,
+}
+
+impl<'a, T> ListIter<'a, T> {
+    #[inline]
+    fn step(&mut self, index: ListIndex) -> Option<&'a T> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        let item = self.list.get(index)?;
+        if self.next == self.prev {
+            self.remaining = Some(0);
+            return Some(item);
+        }
+        if index == self.next {
+            self.next = self.list.next_index(index);
+        } else {
+            self.prev = self.list.prev_index(index);
+        }
+        self.remaining = self.remaining.map(|n| n - 1);
+        Some(item)
+    }
 }
 
 impl<'a, T> Iterator for ListIter<'a, T> {
     type Item = &'a T;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let item = self.list.get(self.next);
-        self.next = self.list.next_index(self.next);
-        item
+        self.step(self.next)
     }
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let my_len = self.list.len();
-        (my_len, Some(my_len))
+        match self.remaining {
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (1, Some(self.list.len())),
+        }
     }
 }
 impl<T> FusedIterator for ListIter<'_, T> {}
 
 impl<'a, T> DoubleEndedIterator for ListIter<'a, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        let item = self.list.get(self.prev);
-        self.prev = self.list.prev_index(self.prev);
-        item
+        self.step(self.prev)
+    }
+}
+
+/// A double-ended iterator over the indexes of all the elements in the list.
+/// It is fused and can be reversed.
+pub struct ListIndexes<'a, T> {
+    pub(crate) list: &'a IndexList<T>,
+    pub(crate) next: ListIndex,
+    pub(crate) prev: ListIndex,
+    pub(crate) remaining: usize,
+}
+
+impl<T> Iterator for ListIndexes<'_, T> {
+    type Item = ListIndex;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let index = self.next;
+        self.next = self.list.next_index(index);
+        Some(index)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<T> FusedIterator for ListIndexes<'_, T> {}
+impl<T> ExactSizeIterator for ListIndexes<'_, T> {}
+
+impl<T> DoubleEndedIterator for ListIndexes<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let index = self.prev;
+        self.prev = self.list.prev_index(index);
+        Some(index)
+    }
+}
+
+/// A double-ended iterator over all the elements in the list, together with
+/// their indexes. It is fused and can be reversed.
+pub struct ListIndexedIter<'a, T>(pub(crate) ListIndexes<'a, T>);
+
+impl<'a, T> Iterator for ListIndexedIter<'a, T> {
+    type Item = (ListIndex, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.0.next()?;
+        Some((index, self.0.list.get(index)?))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for ListIndexedIter<'_, T> {}
+impl<T> ExactSizeIterator for ListIndexedIter<'_, T> {}
+
+impl<T> DoubleEndedIterator for ListIndexedIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.0.next_back()?;
+        Some((index, self.0.list.get(index)?))
+    }
+}
+
+/// A double-ended iterator over mutable references to all the elements in
+/// the list, together with their indexes. It is fused and can be reversed.
+pub struct ListIndexedIterMut<'a, T> {
+    // the borrowed elements, in list order
+    pub(crate) items: vec::IntoIter<(ListIndex, &'a mut T)>,
+}
+
+impl<'a, T> Iterator for ListIndexedIterMut<'a, T> {
+    type Item = (ListIndex, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+impl<T> FusedIterator for ListIndexedIterMut<'_, T> {}
+impl<T> ExactSizeIterator for ListIndexedIterMut<'_, T> {}
+
+impl<T> DoubleEndedIterator for ListIndexedIterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.items.next_back()
+    }
+}
+
+/// A double-ended iterator over mutable references to all the elements in
+/// the list. It is fused and can be reversed.
+pub struct ListIterMut<'a, T>(pub(crate) ListIndexedIterMut<'a, T>);
+
+impl<'a, T> Iterator for ListIterMut<'a, T> {
+    type Item = &'a mut T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, elem)| elem)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for ListIterMut<'_, T> {}
+impl<T> ExactSizeIterator for ListIterMut<'_, T> {}
+
+impl<T> DoubleEndedIterator for ListIterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(_, elem)| elem)
     }
 }
@@ -0,0 +1,432 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the LabeledIndexList type
+//!
+//! A labeled list gives every element a pair of integer labels that increase
+//! in list order, so that two elements can be ordered by comparing labels.
+//! The elements are grouped into runs, called buckets, of at most
+//! `BUCKET_MAX` neighbours. Each bucket has a label among the buckets and each
+//! element has a label within its bucket. A new element takes the label
+//! halfway between its neighbours, and when there is no room left the bucket
+//! is relabeled evenly. Full buckets are split in two, where the new bucket is
+//! labeled by relabeling the smallest surrounding range of bucket labels that
+//! is sparse enough. Since that only happens once per many insertions, the
+//! amortized cost of an insertion is constant.
+use std::{cmp::Ordering, default::Default, fmt, ops::Deref};
+use std::iter::{Extend, FromIterator};
+use crate::{listindex::ListIndex, listiter::ListIterMut, listpolicy::TrimPolicy, IndexList};
+
+const BUCKET_MAX: usize = 64;
+// the bucket labels of a range of 2^i labels must be less dense than
+// DENSITY^-i, where the density must be between 1 and 2
+const DENSITY: f64 = 1.4;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Tag {
+    bucket: usize,
+    label: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Bucket {
+    label: u64,
+    size: usize,
+    first: ListIndex,
+    last: ListIndex,
+}
+
+/// An index list that can tell which of two elements comes first in constant
+/// time.
+///
+/// All the methods of `IndexList` that don't change the list are available
+/// through `Deref`, while the changes must be made with the methods of this
+/// type, so that the labels can be kept up to date.
+pub struct LabeledIndexList<T> {
+    list: IndexList<T>,
+    tags: Vec<Tag>,
+    buckets: Vec<Bucket>,
+    unused: Vec<usize>,
+}
+
+impl<T> Default for LabeledIndexList<T> {
+    fn default() -> Self {
+        LabeledIndexList {
+            list: IndexList::new(),
+            tags: Vec::new(),
+            buckets: Vec::new(),
+            unused: Vec::new(),
+        }
+    }
+}
+
+impl<T> LabeledIndexList<T> {
+    /// Creates a new empty labeled list.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::LabeledIndexList;
+    ///
+    /// let list = LabeledIndexList::<u64>::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Returns a reference to the underlying list.
+    #[inline]
+    pub fn as_list(&self) -> &IndexList<T> {
+        &self.list
+    }
+    /// Returns the underlying list, dropping the labels.
+    #[inline]
+    pub fn into_list(self) -> IndexList<T> {
+        self.list
+    }
+    /// Compare the positions of two elements in the list, in constant time.
+    ///
+    /// Returns `Ordering::Less` if the element at `this` comes before the one
+    /// at `that`, or `None` if either index is not in use.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::LabeledIndexList;
+    /// # use std::cmp::Ordering;
+    /// # let mut list = LabeledIndexList::<u64>::new();
+    /// let last = list.insert_last(2);
+    /// let first = list.insert_first(1);
+    /// assert_eq!(list.compare_positions(first, last), Some(Ordering::Less));
+    /// list.move_to_first(last);
+    /// assert_eq!(list.compare_positions(first, last), Some(Ordering::Greater));
+    /// ```
+    pub fn compare_positions(&self, this: ListIndex, that: ListIndex) -> Option<Ordering> {
+        if !self.list.is_index_used(this) || !self.list.is_index_used(that) {
+            return None;
+        }
+        let (here, there) = (self.tag(this), self.tag(that));
+        let (top_here, top_there) = (self.buckets[here.bucket].label, self.buckets[there.bucket].label);
+        Some(top_here.cmp(&top_there).then(here.label.cmp(&there.label)))
+    }
+    /// Get a mutable reference to the element data at the index, or `None`.
+    #[inline]
+    pub fn get_mut(&mut self, index: ListIndex) -> Option<&mut T> {
+        self.list.get_mut(index)
+    }
+    /// Create a new iterator over mutable references to all the elements.
+    ///
+    /// As with `IndexList::iter_mut`, creating the iterator allocates two
+    /// vectors and takes time in proportion to the capacity.
+    #[inline]
+    pub fn iter_mut(&mut self) -> ListIterMut<'_, T> {
+        self.list.iter_mut()
+    }
+    /// Insert a new element at the beginning.
+    pub fn insert_first(&mut self, elem: T) -> ListIndex {
+        let this = self.list.insert_first(elem);
+        self.label_new(this);
+        this
+    }
+    /// Insert a new element at the end.
+    pub fn insert_last(&mut self, elem: T) -> ListIndex {
+        let this = self.list.insert_last(elem);
+        self.label_new(this);
+        this
+    }
+    /// Insert a new element before the index.
+    ///
+    /// If the index is `None` then the new element will be inserted first.
+    pub fn insert_before(&mut self, index: ListIndex, elem: T) -> ListIndex {
+        let this = self.list.insert_before(index, elem);
+        self.label_new(this);
+        this
+    }
+    /// Insert a new element after the index.
+    ///
+    /// If the index is `None` then the new element will be inserted last.
+    pub fn insert_after(&mut self, index: ListIndex, elem: T) -> ListIndex {
+        let this = self.list.insert_after(index, elem);
+        self.label_new(this);
+        this
+    }
+    /// Remove the first element and return its data.
+    #[inline]
+    pub fn remove_first(&mut self) -> Option<T> {
+        self.remove(self.first_index())
+    }
+    /// Remove the last element and return its data.
+    #[inline]
+    pub fn remove_last(&mut self) -> Option<T> {
+        self.remove(self.last_index())
+    }
+    /// Remove the element at the index and return its data.
+    pub fn remove(&mut self, index: ListIndex) -> Option<T> {
+        if self.list.is_index_used(index) {
+            self.unlabel(index);
+        }
+        self.list.remove(index)
+    }
+    /// Move the element at the index to the beginning.
+    pub fn move_to_first(&mut self, index: ListIndex) {
+        self.relink(index, |list| list.move_to_first(index));
+    }
+    /// Move the element at the index to the end.
+    pub fn move_to_last(&mut self, index: ListIndex) {
+        self.relink(index, |list| list.move_to_last(index));
+    }
+    /// Move the element at the index to just before the element at `that`.
+    pub fn move_before(&mut self, index: ListIndex, that: ListIndex) {
+        if index != that && self.list.is_index_used(that) {
+            self.relink(index, |list| list.move_before(index, that));
+        }
+    }
+    /// Move the element at the index to just after the element at `that`.
+    pub fn move_after(&mut self, index: ListIndex, that: ListIndex) {
+        if index != that && self.list.is_index_used(that) {
+            self.relink(index, |list| list.move_after(index, that));
+        }
+    }
+    /// Clears the list be removing all elements, making it empty.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.tags.clear();
+        self.buckets.clear();
+        self.unused.clear();
+    }
+
+    #[inline]
+    fn tag(&self, index: ListIndex) -> Tag {
+        index.get().map_or_else(Tag::default, |at| self.tags[at])
+    }
+    #[inline]
+    fn set_tag(&mut self, index: ListIndex, tag: Tag) {
+        if let Some(at) = index.get() {
+            if self.tags.len() <= at {
+                self.tags.resize(at + 1, Tag::default());
+            }
+            self.tags[at] = tag;
+        }
+    }
+    fn new_bucket(&mut self, bucket: Bucket) -> usize {
+        match self.unused.pop() {
+            Some(id) => {
+                self.buckets[id] = bucket;
+                id
+            }
+            None => {
+                self.buckets.push(bucket);
+                self.buckets.len() - 1
+            }
+        }
+    }
+    fn next_bucket(&self, id: usize) -> Option<usize> {
+        let next = self.list.next_index(self.buckets[id].last);
+        next.is_some().then(|| self.tag(next).bucket)
+    }
+    fn prev_bucket(&self, id: usize) -> Option<usize> {
+        let prev = self.list.prev_index(self.buckets[id].first);
+        prev.is_some().then(|| self.tag(prev).bucket)
+    }
+    // give a newly linked element a label between its neighbours
+    fn label_new(&mut self, this: ListIndex) {
+        let prev = self.list.prev_index(this);
+        let next = self.list.next_index(this);
+        if prev.is_none() && next.is_none() {
+            let id = self.new_bucket(Bucket { label: u64::MAX / 2, size: 1, first: this, last: this });
+            self.set_tag(this, Tag { bucket: id, label: u64::MAX / 2 });
+            return;
+        }
+        // join the bucket of the previous element, or else the next one
+        let id = if prev.is_some() { self.tag(prev).bucket } else { self.tag(next).bucket };
+        let low = if prev.is_some() { self.tag(prev).label } else { 0 };
+        let high = if next.is_some() && self.tag(next).bucket == id {
+            self.tag(next).label
+        } else {
+            u64::MAX
+        };
+        // join the bucket before relabeling, so that spreading it covers
+        // this element as well
+        let bucket = &mut self.buckets[id];
+        if bucket.last == prev {
+            bucket.last = this;
+        }
+        if bucket.first == next {
+            bucket.first = this;
+        }
+        bucket.size += 1;
+        if high - low < 2 {
+            self.spread_bucket(id);
+        } else {
+            self.set_tag(this, Tag { bucket: id, label: low + (high - low) / 2 });
+        }
+        if self.buckets[id].size > BUCKET_MAX {
+            self.split_bucket(id);
+        }
+    }
+    fn unlabel(&mut self, this: ListIndex) {
+        let id = self.tag(this).bucket;
+        let (prev, next) = (self.list.prev_index(this), self.list.next_index(this));
+        let bucket = &mut self.buckets[id];
+        bucket.size -= 1;
+        if bucket.size == 0 {
+            self.unused.push(id);
+            return;
+        }
+        if bucket.first == this {
+            bucket.first = next;
+        }
+        if bucket.last == this {
+            bucket.last = prev;
+        }
+    }
+    fn relink<F>(&mut self, index: ListIndex, f: F)
+    where
+        F: FnOnce(&mut IndexList<T>),
+    {
+        if self.list.is_index_used(index) {
+            self.unlabel(index);
+            f(&mut self.list);
+            self.label_new(index);
+        }
+    }
+    // give the elements of the bucket evenly spaced labels
+    fn spread_bucket(&mut self, id: usize) {
+        let Bucket { size, first, .. } = self.buckets[id];
+        let gap = u64::MAX / (size as u64 + 1);
+        let mut index = first;
+        for n in 1..=size as u64 {
+            self.set_tag(index, Tag { bucket: id, label: n * gap });
+            index = self.list.next_index(index);
+        }
+    }
+    // move the second half of a full bucket to a new bucket after it
+    fn split_bucket(&mut self, id: usize) {
+        let Bucket { size, first, last, .. } = self.buckets[id];
+        let keep = size / 2;
+        let mut middle = first;
+        for _ in 1..keep {
+            middle = self.list.next_index(middle);
+        }
+        let start = self.list.next_index(middle);
+        let label = self.make_room_after(id);
+        let new = self.new_bucket(Bucket { label, size: size - keep, first: start, last });
+        let bucket = &mut self.buckets[id];
+        bucket.size = keep;
+        bucket.last = middle;
+        self.spread_bucket(id);
+        self.spread_bucket(new);
+    }
+    // find a free bucket label after the bucket, relabeling its surroundings
+    // if needed
+    fn make_room_after(&mut self, id: usize) -> u64 {
+        let low = self.buckets[id].label;
+        let high = self.next_bucket(id).map_or(u64::MAX, |next| self.buckets[next].label);
+        if high - low >= 2 {
+            return low + (high - low) / 2;
+        }
+        for bits in 1..=64u32 {
+            let width = 1u128 << bits;
+            let start = (low as u128) & !(width - 1);
+            let end = start + width;
+            let mut first = id;
+            while let Some(prev) = self.prev_bucket(first) {
+                if (self.buckets[prev].label as u128) < start {
+                    break;
+                }
+                first = prev;
+            }
+            let mut count = 1;
+            let mut bucket = first;
+            while let Some(next) = self.next_bucket(bucket) {
+                if self.buckets[next].label as u128 >= end {
+                    break;
+                }
+                bucket = next;
+                count += 1;
+            }
+            // one more bucket is about to be added
+            let limit = (2.0 / DENSITY).powi(bits as i32);
+            if bits < 64 && (count + 1) as f64 > limit {
+                continue;
+            }
+            let gap = width / (count as u128 + 1);
+            let mut bucket = first;
+            for n in 0..count as u128 {
+                self.buckets[bucket].label = (start + n * gap) as u64;
+                if let Some(next) = self.next_bucket(bucket) {
+                    bucket = next;
+                }
+            }
+            let low = self.buckets[id].label;
+            let high = self.next_bucket(id).map_or(u64::MAX, |next| self.buckets[next].label);
+            return low + (high - low) / 2;
+        }
+        unreachable!()
+    }
+}
+
+impl<T> Deref for LabeledIndexList<T> {
+    type Target = IndexList<T>;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.list
+    }
+}
+
+impl<T> From<IndexList<T>> for LabeledIndexList<T> {
+    fn from(mut list: IndexList<T>) -> Self {
+        // compacting would move the indexes behind the back of the labels
+        list.set_trim_policy(TrimPolicy::Manual);
+        let indexes: Vec<ListIndex> = list.indexes().collect();
+        let mut labeled = LabeledIndexList { list, ..Default::default() };
+        let chunks = indexes.chunks(BUCKET_MAX / 2);
+        let gap = u64::MAX / (chunks.len() as u64 + 1);
+        for (n, chunk) in chunks.enumerate() {
+            let id = labeled.new_bucket(Bucket {
+                label: (n as u64 + 1) * gap,
+                size: chunk.len(),
+                first: chunk[0],
+                last: chunk[chunk.len() - 1],
+            });
+            for &index in chunk {
+                labeled.set_tag(index, Tag { bucket: id, label: 0 });
+            }
+            labeled.spread_bucket(id);
+        }
+        labeled
+    }
+}
+
+impl<T> FromIterator<T> for LabeledIndexList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        LabeledIndexList::from(IndexList::from_iter(iter))
+    }
+}
+
+impl<T> Extend<T> for LabeledIndexList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert_last(elem);
+        }
+    }
+}
+
+impl<T> fmt::Display for LabeledIndexList<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.list.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for LabeledIndexList<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.list.fmt(f)
+    }
+}
@@ -0,0 +1,41 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the ListSlot type
+use std::fmt;
+use crate::{listcheck::ListChain, listindex::ListIndex};
+
+/// The internal state of one index in the list, as returned by `layout`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ListSlot {
+    /// The index of this slot.
+    pub index: ListIndex,
+    /// Whether the slot holds element data.
+    pub occupied: bool,
+    /// The next index on the same chain.
+    pub next: ListIndex,
+    /// The previous index on the same chain.
+    pub prev: ListIndex,
+    /// The chain the slot is on, or `None` for a reserved index.
+    pub chain: Option<ListChain>,
+    /// Whether the walk of the chain was cut off after this slot, because
+    /// the next index had already been walked or is out of bounds.
+    pub cut_off: bool,
+}
+
+impl fmt::Display for ListSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chain = match self.chain {
+            Some(chain) => chain.to_string(),
+            None => String::from("reserved"),
+        };
+        let data = if self.occupied { "data" } else { "empty" };
+        write!(f, "{}: {} {} {}<>{}", self.index, chain, data, self.next, self.prev)?;
+        if self.cut_off {
+            write!(f, " cut off")?;
+        }
+        Ok(())
+    }
+}
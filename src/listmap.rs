@@ -0,0 +1,511 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the IndexListMap type and its entries
+//!
+//! The map keeps its key and value pairs in an `IndexList`, in the order they
+//! were inserted, and a hash map from each key to the index of its pair. The
+//! list order can be changed in constant time, and iterating follows the list
+//! without hashing any keys.
+use std::{borrow::Borrow, collections::HashMap, default::Default, fmt, hash::Hash, mem};
+use std::iter::{Extend, FromIterator, FusedIterator};
+use std::ops::Index;
+use crate::{listindex::ListIndex, listiter::{ListIter, ListIterMut}, IndexList};
+
+/// A hash map that remembers the order of its entries.
+pub struct IndexListMap<K, V> {
+    list: IndexList<(K, V)>,
+    map: HashMap<K, ListIndex>,
+}
+
+impl<K, V> Default for IndexListMap<K, V> {
+    fn default() -> Self {
+        IndexListMap { list: IndexList::new(), map: HashMap::new() }
+    }
+}
+
+impl<K, V> IndexListMap<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Creates a new empty map.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::IndexListMap;
+    ///
+    /// let map = IndexListMap::<&str, u32>::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Creates a new empty map with room for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexListMap {
+            list: IndexList::with_capacity(capacity),
+            map: HashMap::with_capacity(capacity),
+        }
+    }
+    /// Returns a reference to the underlying list of pairs.
+    #[inline]
+    pub fn as_list(&self) -> &IndexList<(K, V)> {
+        &self.list
+    }
+    /// Returns the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+    /// Returns true when the map is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+    /// Returns true if the key is in the map.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+    /// Returns the index of the entry for the key, or `None`.
+    ///
+    /// The index stays valid until the entry is removed, and can be used for
+    /// positional edits of the underlying list.
+    #[inline]
+    pub fn index_of<Q>(&self, key: &Q) -> ListIndex
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).copied().unwrap_or_default()
+    }
+    /// Get a reference to the value of the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.list.get(index).map(|(_, value)| value)
+    }
+    /// Get a mutable reference to the value of the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.list.get_mut(index).map(|(_, value)| value)
+    }
+    /// Get the key and value of the entry at the index.
+    #[inline]
+    pub fn get_index(&self, index: ListIndex) -> Option<(&K, &V)> {
+        self.list.get(index).map(|(key, value)| (key, value))
+    }
+    /// Get the first entry of the map.
+    #[inline]
+    pub fn front(&self) -> Option<(&K, &V)> {
+        self.get_index(self.list.first_index())
+    }
+    /// Get the last entry of the map.
+    #[inline]
+    pub fn back(&self) -> Option<(&K, &V)> {
+        self.get_index(self.list.last_index())
+    }
+    /// Insert the value for the key and return the old value, if any.
+    ///
+    /// A new key is added at the back, while an existing key keeps its place.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexListMap;
+    /// # let mut map = IndexListMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert_eq!(map.insert("a", 3), Some(1));
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&"a", &3), (&"b", &2)]);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.map.get(&key) {
+            Some(&index) => self.list.get_mut(index).map(|(_, old)| mem::replace(old, value)),
+            None => {
+                let index = self.list.insert_last((key.clone(), value));
+                self.map.insert(key, index);
+                None
+            }
+        }
+    }
+    /// Insert the value for the key at the back, moving the key there if it
+    /// was already in the map, and return the old value, if any.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexListMap;
+    /// # let mut map = IndexListMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert_eq!(map.insert_back("a", 3), Some(1));
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&"b", &2), (&"a", &3)]);
+    /// ```
+    pub fn insert_back(&mut self, key: K, value: V) -> Option<V> {
+        let old = self.insert(key.clone(), value);
+        self.move_to_back(&key);
+        old
+    }
+    /// Insert the value for the key at the front, moving the key there if it
+    /// was already in the map, and return the old value, if any.
+    pub fn insert_front(&mut self, key: K, value: V) -> Option<V> {
+        let old = self.insert(key.clone(), value);
+        self.move_to_front(&key);
+        old
+    }
+    /// Remove the key from the map and return its value.
+    #[inline]
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+    /// Remove the key from the map and return the stored key and its value.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.map.remove(key)?;
+        self.list.remove(index)
+    }
+    /// Move the entry for the key to the front of the map.
+    ///
+    /// Returns false if the key is not in the map.
+    pub fn move_to_front<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&index| self.list.move_to_first(index)).is_some()
+    }
+    /// Move the entry for the key to the back of the map.
+    ///
+    /// Returns false if the key is not in the map.
+    pub fn move_to_back<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&index| self.list.move_to_last(index)).is_some()
+    }
+    /// Remove the first entry of the map and return it.
+    pub fn pop_front(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.remove_first()?;
+        self.map.remove(&key);
+        Some((key, value))
+    }
+    /// Remove the last entry of the map and return it.
+    pub fn pop_back(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.remove_last()?;
+        self.map.remove(&key);
+        Some((key, value))
+    }
+    /// Get the entry for the key, for in-place updates.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexListMap;
+    /// let mut words = IndexListMap::new();
+    /// for word in "the cat saw the dog".split(' ') {
+    ///     *words.entry(word).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(words.iter().collect::<Vec<_>>(),
+    ///            vec![(&"the", &2), (&"cat", &1), (&"saw", &1), (&"dog", &1)]);
+    /// ```
+    pub fn entry(&mut self, key: K) -> MapEntry<'_, K, V> {
+        match self.map.get(&key) {
+            Some(&index) => MapEntry::Occupied(OccupiedMapEntry { map: self, index }),
+            None => MapEntry::Vacant(VacantMapEntry { map: self, key }),
+        }
+    }
+    /// Remove all the entries.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.map.clear();
+    }
+    /// Create an iterator over the keys and values, in order.
+    #[inline]
+    pub fn iter(&self) -> MapIter<'_, K, V> {
+        MapIter(self.list.iter())
+    }
+    /// Create an iterator over the keys and mutable values, in order.
+    ///
+    /// As with `IndexList::iter_mut`, creating the iterator allocates two
+    /// vectors and takes time in proportion to the capacity of the list.
+    #[inline]
+    pub fn iter_mut(&mut self) -> MapIterMut<'_, K, V> {
+        MapIterMut(self.list.iter_mut())
+    }
+    /// Create an iterator over the keys, in order.
+    #[inline]
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+    /// Create an iterator over the values, in order.
+    #[inline]
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<K, V, Q> Index<&Q> for IndexListMap<K, V>
+where
+    K: Hash + Eq + Clone + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+{
+    type Output = V;
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("key is not in the map")
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for IndexListMap<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = IndexListMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> Extend<(K, V)> for IndexListMap<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a IndexListMap<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = MapIter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> fmt::Debug for IndexListMap<K, V>
+where
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// A view into a single key of the map, which is either occupied or vacant.
+pub enum MapEntry<'a, K, V> {
+    Occupied(OccupiedMapEntry<'a, K, V>),
+    Vacant(VacantMapEntry<'a, K, V>),
+}
+
+impl<'a, K, V> MapEntry<'a, K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Returns the key of this entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        match self {
+            MapEntry::Occupied(entry) => entry.key(),
+            MapEntry::Vacant(entry) => entry.key(),
+        }
+    }
+    /// Modify the value if the entry is occupied.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let MapEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+    /// Returns the value, after inserting it at the back if vacant.
+    #[inline]
+    pub fn or_insert(self, value: V) -> &'a mut V {
+        self.or_insert_with(|| value)
+    }
+    /// Returns the value, after inserting the result of the closure at the
+    /// back if vacant.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+    /// Returns the value, after inserting the default value at the back if
+    /// vacant.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+}
+
+/// An entry for a key that is in the map.
+pub struct OccupiedMapEntry<'a, K, V> {
+    map: &'a mut IndexListMap<K, V>,
+    index: ListIndex,
+}
+
+impl<'a, K, V> OccupiedMapEntry<'a, K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Returns the index of the entry in the underlying list.
+    #[inline]
+    pub fn index(&self) -> ListIndex {
+        self.index
+    }
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        &self.map.list[self.index].0
+    }
+    /// Get an immutable reference to the value.
+    #[inline]
+    pub fn get(&self) -> &V {
+        &self.map.list[self.index].1
+    }
+    /// Get a mutable reference to the value.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.list[self.index].1
+    }
+    /// Convert the entry into a mutable reference to the value, with the
+    /// lifetime of the map.
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.list[self.index].1
+    }
+    /// Replace the value and return the old value.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+    /// Remove the entry from the map and return its value.
+    pub fn remove(self) -> V {
+        let (key, value) = self.map.list.remove(self.index).unwrap();
+        self.map.map.remove(&key);
+        value
+    }
+    /// Move the entry to the front of the map.
+    #[inline]
+    pub fn move_to_front(&mut self) {
+        self.map.list.move_to_first(self.index);
+    }
+    /// Move the entry to the back of the map.
+    #[inline]
+    pub fn move_to_back(&mut self) {
+        self.map.list.move_to_last(self.index);
+    }
+}
+
+/// An entry for a key that is not in the map.
+pub struct VacantMapEntry<'a, K, V> {
+    map: &'a mut IndexListMap<K, V>,
+    key: K,
+}
+
+impl<'a, K, V> VacantMapEntry<'a, K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Returns the key that was requested.
+    #[inline]
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+    /// Take ownership of the key.
+    #[inline]
+    pub fn into_key(self) -> K {
+        self.key
+    }
+    /// Insert the value at the back of the map.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self.map.list.insert_last((self.key.clone(), value));
+        self.map.map.insert(self.key, index);
+        &mut self.map.list[index].1
+    }
+    /// Insert the value at the front of the map.
+    pub fn insert_front(self, value: V) -> &'a mut V {
+        let index = self.map.list.insert_first((self.key.clone(), value));
+        self.map.map.insert(self.key, index);
+        &mut self.map.list[index].1
+    }
+}
+
+/// An iterator over the keys and values of an `IndexListMap`, in order.
+pub struct MapIter<'a, K, V>(ListIter<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for MapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key, value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<K, V> FusedIterator for MapIter<'_, K, V> {}
+impl<K, V> ExactSizeIterator for MapIter<'_, K, V> {}
+
+impl<K, V> DoubleEndedIterator for MapIter<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+/// An iterator over the keys and mutable values of an `IndexListMap`, in
+/// order.
+pub struct MapIterMut<'a, K, V>(ListIterMut<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for MapIterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (&*key, value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<K, V> FusedIterator for MapIterMut<'_, K, V> {}
+impl<K, V> ExactSizeIterator for MapIterMut<'_, K, V> {}
+
+impl<K, V> DoubleEndedIterator for MapIterMut<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (&*key, value))
+    }
+}
@@ -0,0 +1,410 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the MultiIndexList type
+//!
+//! A multi list stores every element once, but links it into `N` independent
+//! orders. Each order has its own vector of `ListNode`s, one per slot, and its
+//! own `ListEnds`. An element is always in all the orders, so inserting it
+//! links it into each of them and removing it unlinks it from each of them.
+//! The free chain uses the nodes of the first order.
+use std::{default::Default, fmt, iter::FusedIterator};
+use std::ops::{Index, IndexMut};
+use crate::{listends::ListEnds, listindex::ListIndex, listnode::ListNode};
+use crate::listposition::ListPosition;
+
+/// A list where each element is kept in `N` independent orders.
+pub struct MultiIndexList<T, const N: usize> {
+    elems: Vec<Option<T>>,
+    nodes: [Vec<ListNode>; N],
+    used: [ListEnds; N],
+    free: ListEnds,
+    size: usize,
+}
+
+impl<T, const N: usize> Default for MultiIndexList<T, N> {
+    fn default() -> Self {
+        const { assert!(N > 0, "a multi list needs at least one order") };
+        MultiIndexList {
+            elems: Vec::new(),
+            nodes: std::array::from_fn(|_| Vec::new()),
+            used: std::array::from_fn(|_| ListEnds::new()),
+            free: ListEnds::new(),
+            size: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> MultiIndexList<T, N> {
+    /// Creates a new empty list.
+    ///
+    /// A list without any orders, where `N` is zero, does not compile.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::MultiIndexList;
+    ///
+    /// // tasks in priority order and in arrival order
+    /// let tasks = MultiIndexList::<&str, 2>::new();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use index_list::MultiIndexList;
+    /// let nothing = MultiIndexList::<&str, 0>::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Returns the number of slots, used or free.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.elems.len()
+    }
+    /// Returns the number of elements in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.size
+    }
+    /// Returns true when the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+    /// Returns true if the index is used by an element.
+    #[inline]
+    pub fn is_index_used(&self, index: ListIndex) -> bool {
+        self.get(index).is_some()
+    }
+    /// Get a reference to the element data at the index, or `None`.
+    #[inline]
+    pub fn get(&self, index: ListIndex) -> Option<&T> {
+        self.elems.get(index.get()?)?.as_ref()
+    }
+    /// Get a mutable reference to the element data at the index, or `None`.
+    #[inline]
+    pub fn get_mut(&mut self, index: ListIndex) -> Option<&mut T> {
+        self.elems.get_mut(index.get()?)?.as_mut()
+    }
+    /// Returns the index of the first element in the order, or `None`.
+    ///
+    /// All the methods that take an order panic if it is not less than `N`.
+    #[inline]
+    pub fn first_index(&self, order: usize) -> ListIndex {
+        self.used[order].head
+    }
+    /// Returns the index of the last element in the order, or `None`.
+    #[inline]
+    pub fn last_index(&self, order: usize) -> ListIndex {
+        self.used[order].tail
+    }
+    /// Returns the index of the next element in the order, or `None`.
+    #[inline]
+    pub fn next_index(&self, order: usize, index: ListIndex) -> ListIndex {
+        self.used_node(order, index).map_or_else(ListIndex::new, |node| node.next)
+    }
+    /// Returns the index of the previous element in the order, or `None`.
+    #[inline]
+    pub fn prev_index(&self, order: usize, index: ListIndex) -> ListIndex {
+        self.used_node(order, index).map_or_else(ListIndex::new, |node| node.prev)
+    }
+    /// Insert a new element at the beginning of every order.
+    pub fn insert_first(&mut self, elem: T) -> ListIndex {
+        let this = self.new_node(elem);
+        for order in 0..N {
+            self.linkin_first(order, this);
+        }
+        this
+    }
+    /// Insert a new element at the end of every order.
+    pub fn insert_last(&mut self, elem: T) -> ListIndex {
+        let this = self.new_node(elem);
+        for order in 0..N {
+            self.linkin_last(order, this);
+        }
+        this
+    }
+    /// Insert a new element at the given position in each of the orders.
+    ///
+    /// A position before or after `None` is the same as first or last. If a
+    /// position refers to an index that is not in use, the element is
+    /// handed back.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{ListPosition, MultiIndexList};
+    /// # let mut tasks = MultiIndexList::<&str, 2>::new();
+    /// const PRIORITY: usize = 0;
+    /// const ARRIVAL: usize = 1;
+    /// let low = tasks.insert_last("low");
+    /// let high = tasks.insert([ListPosition::Before(low), ListPosition::Last], "high")
+    ///     .unwrap();
+    /// assert_eq!(tasks.iter(PRIORITY).copied().collect::<Vec<_>>(), vec!["high", "low"]);
+    /// assert_eq!(tasks.iter(ARRIVAL).copied().collect::<Vec<_>>(), vec!["low", "high"]);
+    /// tasks.remove(high);
+    /// assert_eq!(tasks.first_index(PRIORITY), tasks.first_index(ARRIVAL));
+    /// ```
+    pub fn insert(&mut self, positions: [ListPosition; N], elem: T) -> Result<ListIndex, T> {
+        let valid = positions.iter().all(|position| match position {
+            ListPosition::Before(that) | ListPosition::After(that) => {
+                that.is_none() || self.is_index_used(*that)
+            }
+            ListPosition::First | ListPosition::Last => true,
+        });
+        if !valid {
+            return Err(elem);
+        }
+        let this = self.new_node(elem);
+        for (order, position) in positions.into_iter().enumerate() {
+            self.linkin_at(order, this, position);
+        }
+        Ok(this)
+    }
+    /// Remove the element at the index from all the orders and return its
+    /// data.
+    pub fn remove(&mut self, index: ListIndex) -> Option<T> {
+        let elem = self.elems.get_mut(index.get()?)?.take()?;
+        for order in 0..N {
+            self.linkout_used(order, index);
+        }
+        self.linkin_free(index);
+        self.size -= 1;
+        Some(elem)
+    }
+    /// Remove the first element in the order and return its data.
+    #[inline]
+    pub fn remove_first(&mut self, order: usize) -> Option<T> {
+        self.remove(self.first_index(order))
+    }
+    /// Remove the last element in the order and return its data.
+    #[inline]
+    pub fn remove_last(&mut self, order: usize) -> Option<T> {
+        self.remove(self.last_index(order))
+    }
+    /// Move the element at the index to the beginning of the order, leaving
+    /// it in place in the other orders.
+    pub fn move_to_first(&mut self, order: usize, index: ListIndex) {
+        self.move_to(order, index, ListPosition::First);
+    }
+    /// Move the element at the index to the end of the order, leaving it in
+    /// place in the other orders.
+    pub fn move_to_last(&mut self, order: usize, index: ListIndex) {
+        self.move_to(order, index, ListPosition::Last);
+    }
+    /// Move the element at the index to just before the element at `that` in
+    /// the order, leaving it in place in the other orders.
+    ///
+    /// Returns false, and moves nothing, unless both indexes are used and
+    /// different. Unlike with `move_to`, a `that` of `None` is not the same as
+    /// first.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::{ListIndex, MultiIndexList};
+    /// # let mut list = MultiIndexList::<&str, 2>::new();
+    /// let a = list.insert_last("A");
+    /// let b = list.insert_last("B");
+    /// assert!(list.move_before(0, b, a));
+    /// assert!(!list.move_before(0, a, ListIndex::new()));
+    /// assert_eq!(list.iter(0).copied().collect::<Vec<_>>(), vec!["B", "A"]);
+    /// assert_eq!(list.iter(1).copied().collect::<Vec<_>>(), vec!["A", "B"]);
+    /// ```
+    pub fn move_before(&mut self, order: usize, index: ListIndex, that: ListIndex) -> bool {
+        that.is_some() && self.move_to(order, index, ListPosition::Before(that))
+    }
+    /// Move the element at the index to just after the element at `that` in
+    /// the order, leaving it in place in the other orders.
+    ///
+    /// Returns false, and moves nothing, unless both indexes are used and
+    /// different. Unlike with `move_to`, a `that` of `None` is not the same as
+    /// last.
+    pub fn move_after(&mut self, order: usize, index: ListIndex, that: ListIndex) -> bool {
+        that.is_some() && self.move_to(order, index, ListPosition::After(that))
+    }
+    /// Move the element at the index to the position in the order, leaving
+    /// it in place in the other orders.
+    ///
+    /// Returns false, and moves nothing, if the index, or the index of the
+    /// position, is not in use, or if the position is relative to the index
+    /// itself.
+    pub fn move_to(&mut self, order: usize, index: ListIndex, position: ListPosition) -> bool {
+        let anchor = match position {
+            ListPosition::Before(that) | ListPosition::After(that) => that,
+            ListPosition::First | ListPosition::Last => ListIndex::new(),
+        };
+        if !self.is_index_used(index)
+            || anchor == index
+            || (anchor.is_some() && !self.is_index_used(anchor))
+        {
+            return false;
+        }
+        self.linkout_used(order, index);
+        self.linkin_at(order, index, position);
+        true
+    }
+    /// Create an iterator over the indexes of the elements in the order.
+    #[inline]
+    pub fn indexes(&self, order: usize) -> MultiIndexes<'_, T, N> {
+        MultiIndexes {
+            list: self,
+            order,
+            next: self.first_index(order),
+            prev: self.last_index(order),
+            remaining: self.size,
+        }
+    }
+    /// Create an iterator over the elements in the order.
+    #[inline]
+    pub fn iter(&self, order: usize) -> MultiIter<'_, T, N> {
+        MultiIter(self.indexes(order))
+    }
+    /// Clears the list be removing all elements, making it empty.
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+
+    #[inline]
+    fn used_node(&self, order: usize, index: ListIndex) -> Option<&ListNode> {
+        self.is_index_used(index).then(|| &self.nodes[order][index.get().unwrap()])
+    }
+    fn new_node(&mut self, elem: T) -> ListIndex {
+        self.size += 1;
+        let reuse = self.free.head;
+        if let Some(at) = reuse.get() {
+            self.free.unlink(&mut self.nodes[0], reuse);
+            self.elems[at] = Some(elem);
+            return reuse;
+        }
+        self.elems.push(Some(elem));
+        self.nodes.iter_mut().for_each(|nodes| nodes.push(ListNode::new()));
+        ListIndex::from(self.elems.len() - 1)
+    }
+    fn linkin_free(&mut self, this: ListIndex) {
+        self.free.link_last(&mut self.nodes[0], this);
+    }
+    fn linkin_at(&mut self, order: usize, this: ListIndex, position: ListPosition) {
+        let (used, nodes) = (&mut self.used[order], &mut self.nodes[order]);
+        match position {
+            ListPosition::Before(that) if that.is_some() => used.link_before(nodes, this, that),
+            ListPosition::After(that) if that.is_some() => used.link_after(nodes, this, that),
+            ListPosition::First | ListPosition::Before(_) => used.link_first(nodes, this),
+            ListPosition::Last | ListPosition::After(_) => used.link_last(nodes, this),
+        }
+    }
+    fn linkin_first(&mut self, order: usize, this: ListIndex) {
+        self.used[order].link_first(&mut self.nodes[order], this);
+    }
+    fn linkin_last(&mut self, order: usize, this: ListIndex) {
+        self.used[order].link_last(&mut self.nodes[order], this);
+    }
+    // prev >< this >< next => prev >< next
+    fn linkout_used(&mut self, order: usize, this: ListIndex) {
+        self.used[order].unlink(&mut self.nodes[order], this);
+    }
+}
+
+impl<T, const N: usize> Index<ListIndex> for MultiIndexList<T, N> {
+    type Output = T;
+    fn index(&self, index: ListIndex) -> &Self::Output {
+        self.get(index).unwrap_or_else(|| panic!("index {} is not in use", index))
+    }
+}
+
+impl<T, const N: usize> IndexMut<ListIndex> for MultiIndexList<T, N> {
+    fn index_mut(&mut self, index: ListIndex) -> &mut Self::Output {
+        self.get_mut(index).unwrap_or_else(|| panic!("index {} is not in use", index))
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for MultiIndexList<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut orders = f.debug_list();
+        for order in 0..N {
+            orders.entry(&DebugOrder(self, order));
+        }
+        orders.finish()
+    }
+}
+
+struct DebugOrder<'a, T, const N: usize>(&'a MultiIndexList<T, N>, usize);
+
+impl<T, const N: usize> fmt::Debug for DebugOrder<'_, T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.0.iter(self.1)).finish()
+    }
+}
+
+/// An iterator over the indexes of the elements of a `MultiIndexList`, in one
+/// of its orders.
+pub struct MultiIndexes<'a, T, const N: usize> {
+    list: &'a MultiIndexList<T, N>,
+    order: usize,
+    next: ListIndex,
+    prev: ListIndex,
+    remaining: usize,
+}
+
+impl<T, const N: usize> Iterator for MultiIndexes<'_, T, N> {
+    type Item = ListIndex;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let this = self.next;
+        self.next = self.list.next_index(self.order, this);
+        Some(this)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<T, const N: usize> FusedIterator for MultiIndexes<'_, T, N> {}
+impl<T, const N: usize> ExactSizeIterator for MultiIndexes<'_, T, N> {}
+
+impl<T, const N: usize> DoubleEndedIterator for MultiIndexes<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let this = self.prev;
+        self.prev = self.list.prev_index(self.order, this);
+        Some(this)
+    }
+}
+
+/// An iterator over the elements of a `MultiIndexList`, in one of its orders.
+pub struct MultiIter<'a, T, const N: usize>(MultiIndexes<'a, T, N>);
+
+impl<'a, T, const N: usize> Iterator for MultiIter<'a, T, N> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let list = self.0.list;
+        self.0.next().and_then(|index| list.get(index))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T, const N: usize> FusedIterator for MultiIter<'_, T, N> {}
+impl<T, const N: usize> ExactSizeIterator for MultiIter<'_, T, N> {}
+
+impl<T, const N: usize> DoubleEndedIterator for MultiIter<'_, T, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let list = self.0.list;
+        self.0.next_back().and_then(|index| list.get(index))
+    }
+}
@@ -29,6 +29,25 @@ impl ListNode {
     }
 }
 
+// set the previous index of the node at the index, if there is one, and
+// return the old previous index
+#[inline]
+pub fn set_prev(nodes: &mut [ListNode], index: ListIndex, new_prev: ListIndex) -> ListIndex {
+    match index.get() {
+        Some(at) => nodes[at].new_prev(new_prev),
+        None => index,
+    }
+}
+// set the next index of the node at the index, if there is one, and return
+// the old next index
+#[inline]
+pub fn set_next(nodes: &mut [ListNode], index: ListIndex, new_next: ListIndex) -> ListIndex {
+    match index.get() {
+        Some(at) => nodes[at].new_next(new_next),
+        None => index,
+    }
+}
+
 impl fmt::Display for ListNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}<>{}", self.next, self.prev)
@@ -0,0 +1,100 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definitions of the list policy types
+use std::{cmp::Reverse, collections::BinaryHeap, default::Default};
+use std::sync::{Mutex, PoisonError};
+use crate::listindex::ListIndex;
+
+/// The order in which unused indexes are reused by new elements.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReusePolicy {
+    /// Reuse the index that has been unused the longest.
+    #[default]
+    Fifo,
+    /// Reuse the most recently freed index, which is likely still in the
+    /// cache.
+    Lifo,
+    /// Reuse the lowest unused index, which keeps the elements packed at the
+    /// start of the vector so that `trim_safe` can release the free ones at
+    /// the end.
+    LowestFirst,
+}
+
+/// The state of the policies a list has opted into. A list that keeps the
+/// default policies has none, so that it does not pay for them.
+#[derive(Default)]
+pub(crate) struct PolicyState {
+    pub(crate) reuse: ReusePolicy,
+    // candidates for the lowest free index, which may include stale entries
+    pub(crate) lowest_free: BinaryHeap<Reverse<usize>>,
+    pub(crate) trim: TrimPolicy,
+    pub(crate) trim_stats: TrimStats,
+    pub(crate) relocate: Option<Relocator>,
+}
+
+/// When the list should trim its capacity by itself after elements have been
+/// removed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TrimPolicy {
+    /// Never trim automatically, only when `trim_safe` or `trim_swap` is
+    /// called.
+    #[default]
+    Manual,
+    /// Call `trim_safe` when the length drops below `min_load` times the
+    /// capacity, where `min_load` is above 0 and at most 1. No valid index is
+    /// changed.
+    Safe { min_load: f32 },
+    /// Like `Safe`, but also call `trim_swap` when that was not enough to
+    /// bring the load above `min_load`. This may move elements to other
+    /// indexes, which is reported to the relocation callback, and releases
+    /// any reserved indexes.
+    Compact { min_load: f32 },
+}
+
+impl TrimPolicy {
+    // keep the load within (0, 1], where a load that can never be reached
+    // means no trimming at all
+    pub(crate) fn clamped(self) -> Self {
+        match self {
+            TrimPolicy::Safe { min_load } if min_load > 0.0 => {
+                TrimPolicy::Safe { min_load: min_load.min(1.0) }
+            }
+            TrimPolicy::Compact { min_load } if min_load > 0.0 => {
+                TrimPolicy::Compact { min_load: min_load.min(1.0) }
+            }
+            _ => TrimPolicy::Manual,
+        }
+    }
+}
+
+/// Counters for the automatic trimming of a list.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TrimStats {
+    /// The number of times the capacity was reduced automatically.
+    pub runs: usize,
+    /// The number of bytes of heap memory released automatically.
+    pub bytes_reclaimed: usize,
+}
+
+/// A callback that is told the old and new index of a moved element.
+///
+/// The mutex keeps the list `Sync` without asking the same of the callback.
+/// It is only ever reached through `&mut`, so it is never locked.
+pub(crate) struct Relocator(Mutex<Box<dyn FnMut(ListIndex, ListIndex) + Send>>);
+
+impl Relocator {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: FnMut(ListIndex, ListIndex) + Send + 'static,
+    {
+        Relocator(Mutex::new(Box::new(f)))
+    }
+    #[inline]
+    pub(crate) fn relocated(&mut self, old: ListIndex, new: ListIndex) {
+        let f = self.0.get_mut().unwrap_or_else(PoisonError::into_inner);
+        f(old, new)
+    }
+}
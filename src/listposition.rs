@@ -0,0 +1,20 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the ListPosition type
+use crate::listindex::ListIndex;
+
+/// A position in the list where an element can be linked in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ListPosition {
+    /// At the beginning of the list.
+    First,
+    /// At the end of the list.
+    Last,
+    /// Just before the element at the index.
+    Before(ListIndex),
+    /// Just after the element at the index.
+    After(ListIndex),
+}
@@ -0,0 +1,443 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the RankedIndexList type
+//!
+//! A ranked list keeps an implicit treap over the indexes of the list, where
+//! an in-order walk of the tree is the list order and each tree node knows
+//! the size of its subtree. That turns positional lookups into tree walks of
+//! expected logarithmic length, in return for maintaining the tree on every
+//! change to the list order.
+use std::{cmp::Ordering, default::Default, fmt, ops::Deref};
+use std::iter::{Extend, FromIterator};
+use crate::{listindex::ListIndex, listiter::ListIterMut, listpolicy::TrimPolicy, IndexList};
+
+#[derive(Clone, Debug, Default)]
+struct RankNode {
+    left: ListIndex,
+    right: ListIndex,
+    parent: ListIndex,
+    size: u32,
+    priority: u32,
+}
+
+#[derive(Debug)]
+struct RankTree {
+    nodes: Vec<RankNode>,
+    root: ListIndex,
+    seed: u32,
+}
+
+impl Default for RankTree {
+    fn default() -> Self {
+        RankTree {
+            nodes: Vec::new(),
+            root: ListIndex::new(),
+            seed: 0x9e37_79b9,
+        }
+    }
+}
+
+impl RankTree {
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.root = ListIndex::new();
+    }
+    #[inline]
+    fn size(&self, tree: ListIndex) -> usize {
+        tree.get().map_or(0, |at| self.nodes[at].size as usize)
+    }
+    #[inline]
+    fn set_parent(&mut self, tree: ListIndex, parent: ListIndex) {
+        if let Some(at) = tree.get() {
+            self.nodes[at].parent = parent;
+        }
+    }
+    // recompute the size of the subtree and adopt its children
+    fn update(&mut self, tree: ListIndex) {
+        if let Some(at) = tree.get() {
+            let (left, right) = (self.nodes[at].left, self.nodes[at].right);
+            self.nodes[at].size = (1 + self.size(left) + self.size(right)) as u32;
+            self.set_parent(left, tree);
+            self.set_parent(right, tree);
+        }
+    }
+    fn next_priority(&mut self) -> u32 {
+        // xorshift, as the priorities only need to look random
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed
+    }
+    // split the tree into the first `count` nodes and the rest
+    //
+    // split and merge recurse once per level of the tree, and the random
+    // priorities keep a treap about 3 ln(n) levels deep, so the stack stays
+    // shallow for any list that fits in memory
+    fn split(&mut self, tree: ListIndex, count: usize) -> (ListIndex, ListIndex) {
+        let at = match tree.get() {
+            Some(at) => at,
+            None => return (ListIndex::new(), ListIndex::new()),
+        };
+        let left = self.nodes[at].left;
+        let (first, rest) = if count <= self.size(left) {
+            let (first, rest) = self.split(left, count);
+            self.nodes[at].left = rest;
+            self.update(tree);
+            (first, tree)
+        } else {
+            let right = self.nodes[at].right;
+            let (first, rest) = self.split(right, count - self.size(left) - 1);
+            self.nodes[at].right = first;
+            self.update(tree);
+            (tree, rest)
+        };
+        self.set_parent(first, ListIndex::new());
+        self.set_parent(rest, ListIndex::new());
+        (first, rest)
+    }
+    // join two trees where all of `first` comes before `rest`
+    fn merge(&mut self, first: ListIndex, rest: ListIndex) -> ListIndex {
+        let (here, there) = match (first.get(), rest.get()) {
+            (None, _) => return rest,
+            (_, None) => return first,
+            (Some(here), Some(there)) => (here, there),
+        };
+        if self.nodes[here].priority > self.nodes[there].priority {
+            let right = self.nodes[here].right;
+            self.nodes[here].right = self.merge(right, rest);
+            self.update(first);
+            first
+        } else {
+            let left = self.nodes[there].left;
+            self.nodes[there].left = self.merge(first, left);
+            self.update(rest);
+            rest
+        }
+    }
+    fn insert(&mut self, index: ListIndex, position: usize) {
+        let at = match index.get() {
+            Some(at) => at,
+            None => return,
+        };
+        if self.nodes.len() <= at {
+            self.nodes.resize(at + 1, RankNode::default());
+        }
+        let priority = self.next_priority();
+        self.nodes[at] = RankNode { size: 1, priority, ..Default::default() };
+        let (first, rest) = self.split(self.root, position);
+        let first = self.merge(first, index);
+        self.root = self.merge(first, rest);
+        self.set_parent(self.root, ListIndex::new());
+    }
+    fn remove(&mut self, index: ListIndex) {
+        let position = self.position(index);
+        let (first, rest) = self.split(self.root, position);
+        let (this, rest) = self.split(rest, 1);
+        debug_assert_eq!(this, index);
+        self.root = self.merge(first, rest);
+        self.set_parent(self.root, ListIndex::new());
+    }
+    fn position(&self, index: ListIndex) -> usize {
+        let mut this = index;
+        let mut position = this.get().map_or(0, |at| self.size(self.nodes[at].left));
+        while let Some(at) = this.get() {
+            let parent = self.nodes[at].parent;
+            if let Some(up) = parent.get() {
+                if self.nodes[up].right == this {
+                    position += self.size(self.nodes[up].left) + 1;
+                }
+            }
+            this = parent;
+        }
+        position
+    }
+    fn nth(&self, position: usize) -> ListIndex {
+        let mut position = position;
+        let mut this = self.root;
+        while let Some(at) = this.get() {
+            let left = self.nodes[at].left;
+            let before = self.size(left);
+            match position.cmp(&before) {
+                Ordering::Less => this = left,
+                Ordering::Equal => return this,
+                Ordering::Greater => {
+                    position -= before + 1;
+                    this = self.nodes[at].right;
+                }
+            }
+        }
+        this
+    }
+}
+
+/// An index list that also finds elements by their position, and positions
+/// by their index, in logarithmic time.
+///
+/// All the methods of `IndexList` that don't change the list are available
+/// through `Deref`, while the changes must be made with the methods of this
+/// type, so that the positions can be kept up to date.
+pub struct RankedIndexList<T> {
+    list: IndexList<T>,
+    tree: RankTree,
+}
+
+impl<T> Default for RankedIndexList<T> {
+    fn default() -> Self {
+        RankedIndexList {
+            list: IndexList::new(),
+            tree: RankTree::default(),
+        }
+    }
+}
+
+impl<T> RankedIndexList<T> {
+    /// Creates a new empty ranked list.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::RankedIndexList;
+    ///
+    /// let list = RankedIndexList::<u64>::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Returns a reference to the underlying list.
+    #[inline]
+    pub fn as_list(&self) -> &IndexList<T> {
+        &self.list
+    }
+    /// Returns the underlying list, dropping the positions.
+    #[inline]
+    pub fn into_list(self) -> IndexList<T> {
+        self.list
+    }
+    /// Returns the index of the element at the position, counting from zero,
+    /// or `None` if the position is beyond the end.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::RankedIndexList;
+    /// # let list: RankedIndexList<u64> = (0..100).collect();
+    /// let index = list.nth(42);
+    /// assert_eq!(list.get(index), Some(&42));
+    /// ```
+    #[inline]
+    pub fn nth(&self, position: usize) -> ListIndex {
+        self.tree.nth(position)
+    }
+    /// Returns the position of the element at the index, counting from zero,
+    /// or `None` if the index is not in use.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::RankedIndexList;
+    /// # let mut list: RankedIndexList<u64> = (0..100).collect();
+    /// let index = list.nth(42);
+    /// list.remove_first();
+    /// assert_eq!(list.position_of(index), Some(41));
+    /// ```
+    #[inline]
+    pub fn position_of(&self, index: ListIndex) -> Option<usize> {
+        if self.list.is_index_used(index) {
+            Some(self.tree.position(index))
+        } else {
+            None
+        }
+    }
+    /// Get a mutable reference to the element data at the index, or `None`.
+    #[inline]
+    pub fn get_mut(&mut self, index: ListIndex) -> Option<&mut T> {
+        self.list.get_mut(index)
+    }
+    /// Create a new iterator over mutable references to all the elements.
+    ///
+    /// As with `IndexList::iter_mut`, creating the iterator allocates two
+    /// vectors and takes time in proportion to the capacity.
+    #[inline]
+    pub fn iter_mut(&mut self) -> ListIterMut<'_, T> {
+        self.list.iter_mut()
+    }
+    /// Insert a new element so that it ends up at the position, or last if
+    /// the position is beyond the end.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::RankedIndexList;
+    /// # let mut list: RankedIndexList<u64> = (0..4).collect();
+    /// let index = list.insert_at(2, 42);
+    /// assert_eq!(list.position_of(index), Some(2));
+    /// assert_eq!(list.to_string(), "[0 >< 1 >< 42 >< 2 >< 3]");
+    /// ```
+    pub fn insert_at(&mut self, position: usize, elem: T) -> ListIndex {
+        let that = self.nth(position);
+        let this = if that.is_some() {
+            self.list.insert_before(that, elem)
+        } else {
+            self.list.insert_last(elem)
+        };
+        self.tree.insert(this, position.min(self.list.len() - 1));
+        this
+    }
+    /// Remove the element at the position and return its data.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::RankedIndexList;
+    /// # let mut list: RankedIndexList<u64> = (0..4).collect();
+    /// assert_eq!(list.remove_at(2), Some(2));
+    /// assert_eq!(list.remove_at(3), None);
+    /// ```
+    #[inline]
+    pub fn remove_at(&mut self, position: usize) -> Option<T> {
+        self.remove(self.nth(position))
+    }
+    /// Insert a new element at the beginning.
+    pub fn insert_first(&mut self, elem: T) -> ListIndex {
+        let this = self.list.insert_first(elem);
+        self.tree.insert(this, 0);
+        this
+    }
+    /// Insert a new element at the end.
+    pub fn insert_last(&mut self, elem: T) -> ListIndex {
+        let this = self.list.insert_last(elem);
+        self.tree.insert(this, self.list.len() - 1);
+        this
+    }
+    /// Insert a new element before the index.
+    ///
+    /// If the index is `None` then the new element will be inserted first.
+    pub fn insert_before(&mut self, index: ListIndex, elem: T) -> ListIndex {
+        let this = self.list.insert_before(index, elem);
+        self.link_tree(this);
+        this
+    }
+    /// Insert a new element after the index.
+    ///
+    /// If the index is `None` then the new element will be inserted last.
+    pub fn insert_after(&mut self, index: ListIndex, elem: T) -> ListIndex {
+        let this = self.list.insert_after(index, elem);
+        self.link_tree(this);
+        this
+    }
+    /// Remove the first element and return its data.
+    #[inline]
+    pub fn remove_first(&mut self) -> Option<T> {
+        self.remove(self.first_index())
+    }
+    /// Remove the last element and return its data.
+    #[inline]
+    pub fn remove_last(&mut self) -> Option<T> {
+        self.remove(self.last_index())
+    }
+    /// Remove the element at the index and return its data.
+    pub fn remove(&mut self, index: ListIndex) -> Option<T> {
+        if self.list.is_index_used(index) {
+            self.tree.remove(index);
+        }
+        self.list.remove(index)
+    }
+    /// Move the element at the index to the beginning.
+    pub fn move_to_first(&mut self, index: ListIndex) {
+        self.relink(index, |list| list.move_to_first(index));
+    }
+    /// Move the element at the index to the end.
+    pub fn move_to_last(&mut self, index: ListIndex) {
+        self.relink(index, |list| list.move_to_last(index));
+    }
+    /// Move the element at the index to just before the element at `that`.
+    pub fn move_before(&mut self, index: ListIndex, that: ListIndex) {
+        if index != that && self.list.is_index_used(that) {
+            self.relink(index, |list| list.move_before(index, that));
+        }
+    }
+    /// Move the element at the index to just after the element at `that`.
+    pub fn move_after(&mut self, index: ListIndex, that: ListIndex) {
+        if index != that && self.list.is_index_used(that) {
+            self.relink(index, |list| list.move_after(index, that));
+        }
+    }
+    /// Clears the list be removing all elements, making it empty.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.tree.clear();
+    }
+
+    // add a newly linked element to the tree, after its previous element
+    fn link_tree(&mut self, this: ListIndex) {
+        let prev = self.list.prev_index(this);
+        let position = if prev.is_some() { self.tree.position(prev) + 1 } else { 0 };
+        self.tree.insert(this, position);
+    }
+    fn relink<F>(&mut self, index: ListIndex, f: F)
+    where
+        F: FnOnce(&mut IndexList<T>),
+    {
+        if self.list.is_index_used(index) {
+            self.tree.remove(index);
+            f(&mut self.list);
+            self.link_tree(index);
+        }
+    }
+}
+
+impl<T> Deref for RankedIndexList<T> {
+    type Target = IndexList<T>;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.list
+    }
+}
+
+/// Rank the elements of the list, in their current order.
+///
+/// The trim policy of the list is replaced by `TrimPolicy::Manual`, since
+/// compacting would move the indexes behind the back of the tree. The list
+/// returned by `into_list` keeps that policy.
+impl<T> From<IndexList<T>> for RankedIndexList<T> {
+    fn from(mut list: IndexList<T>) -> Self {
+        list.set_trim_policy(TrimPolicy::Manual);
+        let mut tree = RankTree::default();
+        for (position, index) in list.indexes().enumerate() {
+            tree.insert(index, position);
+        }
+        RankedIndexList { list, tree }
+    }
+}
+
+impl<T> FromIterator<T> for RankedIndexList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        RankedIndexList::from(IndexList::from_iter(iter))
+    }
+}
+
+impl<T> Extend<T> for RankedIndexList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert_last(elem);
+        }
+    }
+}
+
+impl<T> fmt::Display for RankedIndexList<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.list.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for RankedIndexList<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.list.fmt(f)
+    }
+}
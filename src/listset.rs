@@ -0,0 +1,353 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the IndexListSet type
+//!
+//! The set keeps its values in an `IndexList`, in the order they were
+//! inserted, and a hash map from each value to its index. Looking up a value
+//! costs one hash, and the index can then be used to edit the list order in
+//! constant time.
+use std::{borrow::Borrow, collections::HashMap, default::Default, fmt, hash::Hash};
+use std::iter::{Extend, FromIterator, FusedIterator};
+use crate::{listindex::ListIndex, listiter::ListIter, IndexList};
+
+/// A hash set that remembers the order of its values.
+pub struct IndexListSet<T> {
+    list: IndexList<T>,
+    map: HashMap<T, ListIndex>,
+}
+
+impl<T> Default for IndexListSet<T> {
+    fn default() -> Self {
+        IndexListSet { list: IndexList::new(), map: HashMap::new() }
+    }
+}
+
+impl<T> IndexListSet<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Creates a new empty set.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::IndexListSet;
+    ///
+    /// let set = IndexListSet::<u32>::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Creates a new empty set with room for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexListSet {
+            list: IndexList::with_capacity(capacity),
+            map: HashMap::with_capacity(capacity),
+        }
+    }
+    /// Returns a reference to the underlying list of values.
+    #[inline]
+    pub fn as_list(&self) -> &IndexList<T> {
+        &self.list
+    }
+    /// Returns the number of values in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+    /// Returns true when the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+    /// Returns true if the value is in the set.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+    /// Returns the index of the value, or `None`.
+    ///
+    /// The index stays valid until the value is removed.
+    #[inline]
+    pub fn index_of<Q>(&self, value: &Q) -> ListIndex
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(value).copied().unwrap_or_default()
+    }
+    /// Get the value at the index.
+    #[inline]
+    pub fn get_index(&self, index: ListIndex) -> Option<&T> {
+        self.list.get(index)
+    }
+    /// Get the first value of the set.
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.list.get_first()
+    }
+    /// Get the last value of the set.
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.list.get_last()
+    }
+    /// Add the value at the back of the set.
+    ///
+    /// Returns false if the value was already in the set, in which case it
+    /// keeps its place.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexListSet;
+    /// # let mut set = IndexListSet::new();
+    /// assert!(set.insert('b'));
+    /// assert!(set.insert('a'));
+    /// assert!(!set.insert('b'));
+    /// assert_eq!(set.iter().collect::<String>(), "ba");
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, value: T) -> bool {
+        self.insert_full(value).1
+    }
+    /// Add the value at the back of the set, and return its index along with
+    /// whether it was added.
+    pub fn insert_full(&mut self, value: T) -> (ListIndex, bool) {
+        match self.map.get(&value) {
+            Some(&index) => (index, false),
+            None => {
+                let index = self.list.insert_last(value.clone());
+                self.map.insert(value, index);
+                (index, true)
+            }
+        }
+    }
+    /// Place the value just before the one at the index and return its
+    /// index, moving it there if it was already in the set.
+    ///
+    /// If the index is `None` then the value is placed first. If it is some
+    /// other index that is not in use, `None` is returned and the set is left
+    /// as it is.
+    pub fn insert_before(&mut self, index: ListIndex, value: T) -> Option<ListIndex> {
+        if index.is_some() && !self.list.is_index_used(index) {
+            return None;
+        }
+        Some(match self.map.get(&value) {
+            Some(&this) => {
+                if index.is_none() {
+                    self.list.move_to_first(this);
+                } else {
+                    self.list.move_before(this, index);
+                }
+                this
+            }
+            None => {
+                let this = self.list.insert_before(index, value.clone());
+                self.map.insert(value, this);
+                this
+            }
+        })
+    }
+    /// Place the value just after the one at the index and return its index,
+    /// moving it there if it was already in the set.
+    ///
+    /// If the index is `None` then the value is placed last. If it is some
+    /// other index that is not in use, `None` is returned and the set is left
+    /// as it is.
+    pub fn insert_after(&mut self, index: ListIndex, value: T) -> Option<ListIndex> {
+        if index.is_some() && !self.list.is_index_used(index) {
+            return None;
+        }
+        Some(match self.map.get(&value) {
+            Some(&this) => {
+                if index.is_none() {
+                    self.list.move_to_last(this);
+                } else {
+                    self.list.move_after(this, index);
+                }
+                this
+            }
+            None => {
+                let this = self.list.insert_after(index, value.clone());
+                self.map.insert(value, this);
+                this
+            }
+        })
+    }
+    /// Remove the value from the set.
+    ///
+    /// Returns false if the value was not in the set.
+    #[inline]
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.take(value).is_some()
+    }
+    /// Remove the value from the set and return the stored value.
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.map.remove(value)?;
+        self.list.remove(index)
+    }
+    /// Remove the value at the index from the set and return it.
+    pub fn remove_index(&mut self, index: ListIndex) -> Option<T> {
+        let value = self.list.remove(index)?;
+        self.map.remove(&value);
+        Some(value)
+    }
+    /// Move the value to the front of the set.
+    ///
+    /// Returns false if the value is not in the set.
+    pub fn move_to_front<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(value).map(|&index| self.list.move_to_first(index)).is_some()
+    }
+    /// Move the value to the back of the set.
+    ///
+    /// Returns false if the value is not in the set.
+    pub fn move_to_back<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(value).map(|&index| self.list.move_to_last(index)).is_some()
+    }
+    /// Move the value at the index to just before the value at `that`.
+    #[inline]
+    pub fn move_before(&mut self, index: ListIndex, that: ListIndex) {
+        self.list.move_before(index, that);
+    }
+    /// Move the value at the index to just after the value at `that`.
+    #[inline]
+    pub fn move_after(&mut self, index: ListIndex, that: ListIndex) {
+        self.list.move_after(index, that);
+    }
+    /// Remove the first value of the set and return it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let value = self.list.remove_first()?;
+        self.map.remove(&value);
+        Some(value)
+    }
+    /// Remove the last value of the set and return it.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let value = self.list.remove_last()?;
+        self.map.remove(&value);
+        Some(value)
+    }
+    /// Remove all the values.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.map.clear();
+    }
+    /// Create an iterator over the values, in order.
+    #[inline]
+    pub fn iter(&self) -> SetIter<'_, T> {
+        SetIter(self.list.iter())
+    }
+    /// Create an iterator over the values of this set followed by the values
+    /// of the other set that are not in this one, each in their own order.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexListSet;
+    /// let left: IndexListSet<_> = "dcba".chars().collect();
+    /// let right: IndexListSet<_> = "bdfe".chars().collect();
+    /// assert_eq!(left.union(&right).collect::<String>(), "dcbafe");
+    /// assert_eq!(left.intersection(&right).collect::<String>(), "db");
+    /// assert_eq!(left.difference(&right).collect::<String>(), "ca");
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().chain(other.difference(self))
+    }
+    /// Create an iterator over the values of this set that are also in the
+    /// other set, in the order of this set.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().filter(move |value| other.contains(*value))
+    }
+    /// Create an iterator over the values of this set that are not in the
+    /// other set, in the order of this set.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().filter(move |value| !other.contains(*value))
+    }
+}
+
+impl<T> FromIterator<T> for IndexListSet<T>
+where
+    T: Hash + Eq + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = IndexListSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T> Extend<T> for IndexListSet<T>
+where
+    T: Hash + Eq + Clone,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IndexListSet<T>
+where
+    T: Hash + Eq + Clone,
+{
+    type Item = &'a T;
+    type IntoIter = SetIter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> fmt::Debug for IndexListSet<T>
+where
+    T: Hash + Eq + Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the values of an `IndexListSet`, in order.
+pub struct SetIter<'a, T>(ListIter<'a, T>);
+
+impl<'a, T> Iterator for SetIter<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for SetIter<'_, T> {}
+impl<T> ExactSizeIterator for SetIter<'_, T> {}
+
+impl<T> DoubleEndedIterator for SetIter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definitions of the ListSlotIter types
+use std::iter::{DoubleEndedIterator, Enumerate, ExactSizeIterator, FusedIterator};
+use std::slice;
+use crate::listindex::ListIndex;
+
+/// A double-ended iterator over all the elements in index order, rather than
+/// list order, together with their indexes. It is fused and can be reversed.
+pub struct ListSlotIter<'a, T> {
+    pub(crate) slots: Enumerate<slice::Iter<'a, Option<T>>>,
+    pub(crate) remaining: usize,
+}
+
+impl<'a, T> Iterator for ListSlotIter<'a, T> {
+    type Item = (ListIndex, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (at, elem) = self.slots.find_map(|(at, elem)| Some((at, elem.as_ref()?)))?;
+        self.remaining -= 1;
+        Some((ListIndex::from(at), elem))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<T> FusedIterator for ListSlotIter<'_, T> {}
+impl<T> ExactSizeIterator for ListSlotIter<'_, T> {}
+
+impl<T> DoubleEndedIterator for ListSlotIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (at, elem) = self.slots.by_ref().rev().find_map(|(at, elem)| Some((at, elem.as_ref()?)))?;
+        self.remaining -= 1;
+        Some((ListIndex::from(at), elem))
+    }
+}
+
+/// A double-ended iterator over mutable references to all the elements in
+/// index order, rather than list order, together with their indexes. It is
+/// fused and can be reversed.
+pub struct ListSlotIterMut<'a, T> {
+    pub(crate) slots: Enumerate<slice::IterMut<'a, Option<T>>>,
+    pub(crate) remaining: usize,
+}
+
+impl<'a, T> Iterator for ListSlotIterMut<'a, T> {
+    type Item = (ListIndex, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (at, elem) = self.slots.find_map(|(at, elem)| Some((at, elem.as_mut()?)))?;
+        self.remaining -= 1;
+        Some((ListIndex::from(at), elem))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<T> FusedIterator for ListSlotIterMut<'_, T> {}
+impl<T> ExactSizeIterator for ListSlotIterMut<'_, T> {}
+
+impl<T> DoubleEndedIterator for ListSlotIterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (at, elem) = self.slots.by_ref().rev().find_map(|(at, elem)| Some((at, elem.as_mut()?)))?;
+        self.remaining -= 1;
+        Some((ListIndex::from(at), elem))
+    }
+}
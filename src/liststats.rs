@@ -0,0 +1,33 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the ListStats type
+use std::default::Default;
+
+/// A snapshot of the memory usage and fragmentation of a list.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ListStats {
+    /// The number of elements in the list, the same as `len`.
+    pub used: usize,
+    /// The number of unused indexes, waiting to be reused.
+    pub free: usize,
+    /// The number of indexes that are reserved, but not yet filled.
+    pub reserved: usize,
+    /// The number of indexes, the same as `capacity`.
+    pub capacity: usize,
+    /// The number of unused indexes at the end, which `trim_safe` would
+    /// release.
+    pub trailing_free: usize,
+    /// The share of the capacity that is unused and can only be released by
+    /// `trim_swap`, between 0 and 1.
+    pub fragmentation: f64,
+    /// The bytes of heap memory allocated for the element data.
+    pub elems_bytes: usize,
+    /// The bytes of heap memory allocated for the element links.
+    pub nodes_bytes: usize,
+    /// The average distance between the indexes of neighbouring elements,
+    /// where 1 means that the list order is the same as the index order.
+    pub neighbour_distance: f64,
+}
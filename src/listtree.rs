@@ -0,0 +1,539 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the IndexTree type and its iterators
+//!
+//! The tree applies the index list design to an n-ary tree. Each slot has a
+//! `ListNode` linking it to its previous and next sibling, plus the index of
+//! its parent and the `ListEnds` of its own list of children. The nodes
+//! without a parent, the roots, are siblings in one more list kept by the
+//! tree. Unused slots are linked together through their sibling links on the
+//! free chain, to be reused by new nodes.
+use std::{collections::VecDeque, default::Default, fmt, iter::FusedIterator};
+use std::ops::{Index, IndexMut};
+use crate::{listends::ListEnds, listindex::ListIndex, listnode::ListNode};
+
+#[derive(Clone, Debug, Default)]
+struct TreeLinks {
+    parent: ListIndex,
+    children: ListEnds,
+}
+
+/// A tree where each node can have any number of ordered children, stored in
+/// vectors and linked by indexes.
+pub struct IndexTree<T> {
+    elems: Vec<Option<T>>,
+    nodes: Vec<ListNode>,
+    links: Vec<TreeLinks>,
+    roots: ListEnds,
+    free: ListEnds,
+    size: usize,
+}
+
+impl<T> Default for IndexTree<T> {
+    fn default() -> Self {
+        IndexTree {
+            elems: Vec::new(),
+            nodes: Vec::new(),
+            links: Vec::new(),
+            roots: ListEnds::new(),
+            free: ListEnds::new(),
+            size: 0,
+        }
+    }
+}
+
+impl<T> IndexTree<T> {
+    /// Creates a new empty tree.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::IndexTree;
+    ///
+    /// let tree = IndexTree::<u64>::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Returns the number of slots, used or free.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.elems.len()
+    }
+    /// Returns the number of nodes in the tree.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.size
+    }
+    /// Returns true when the tree is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+    /// Returns true if the index is used by a node.
+    #[inline]
+    pub fn is_index_used(&self, index: ListIndex) -> bool {
+        self.get(index).is_some()
+    }
+    /// Get a reference to the node data at the index, or `None`.
+    #[inline]
+    pub fn get(&self, index: ListIndex) -> Option<&T> {
+        self.elems.get(index.get()?)?.as_ref()
+    }
+    /// Get a mutable reference to the node data at the index, or `None`.
+    #[inline]
+    pub fn get_mut(&mut self, index: ListIndex) -> Option<&mut T> {
+        self.elems.get_mut(index.get()?)?.as_mut()
+    }
+    /// Returns the index of the first root, or `None`.
+    #[inline]
+    pub fn first_root(&self) -> ListIndex {
+        self.roots.head
+    }
+    /// Returns the index of the last root, or `None`.
+    #[inline]
+    pub fn last_root(&self) -> ListIndex {
+        self.roots.tail
+    }
+    /// Returns the index of the parent of the node, or `None` for a root.
+    #[inline]
+    pub fn parent(&self, index: ListIndex) -> ListIndex {
+        self.used_links(index).map_or_else(ListIndex::new, |links| links.parent)
+    }
+    /// Returns the index of the first child of the node, or `None`.
+    #[inline]
+    pub fn first_child(&self, index: ListIndex) -> ListIndex {
+        self.used_links(index).map_or_else(ListIndex::new, |links| links.children.head)
+    }
+    /// Returns the index of the last child of the node, or `None`.
+    #[inline]
+    pub fn last_child(&self, index: ListIndex) -> ListIndex {
+        self.used_links(index).map_or_else(ListIndex::new, |links| links.children.tail)
+    }
+    /// Returns the index of the next sibling of the node, or `None`.
+    #[inline]
+    pub fn next_sibling(&self, index: ListIndex) -> ListIndex {
+        self.used_node(index).map_or_else(ListIndex::new, |node| node.next)
+    }
+    /// Returns the index of the previous sibling of the node, or `None`.
+    #[inline]
+    pub fn prev_sibling(&self, index: ListIndex) -> ListIndex {
+        self.used_node(index).map_or_else(ListIndex::new, |node| node.prev)
+    }
+    /// Returns the number of ancestors of the node, zero for a root.
+    #[inline]
+    pub fn depth(&self, index: ListIndex) -> usize {
+        self.ancestors(index).count()
+    }
+    /// Add a new root after the other roots.
+    pub fn new_root(&mut self, elem: T) -> ListIndex {
+        let this = self.new_node(elem);
+        self.linkin_last(ListIndex::new(), this);
+        this
+    }
+    /// Add a new node as the last child of the parent.
+    ///
+    /// Panics if the parent index is not in use.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexTree;
+    /// # let mut tree = IndexTree::new();
+    /// let root = tree.new_root("/");
+    /// let usr = tree.append_child(root, "usr");
+    /// let bin = tree.append_child(usr, "bin");
+    /// tree.insert_sibling_before(usr, "etc");
+    /// let path: Vec<_> = tree.preorder(root).map(|index| tree[index]).collect();
+    /// assert_eq!(path, vec!["/", "etc", "usr", "bin"]);
+    /// assert_eq!(tree.ancestors(bin).collect::<Vec<_>>(), vec![usr, root]);
+    /// ```
+    pub fn append_child(&mut self, parent: ListIndex, elem: T) -> ListIndex {
+        self.assert_used(parent);
+        let this = self.new_node(elem);
+        self.linkin_last(parent, this);
+        this
+    }
+    /// Add a new node as the sibling just before the node at the index.
+    ///
+    /// Panics if the index is not in use.
+    pub fn insert_sibling_before(&mut self, index: ListIndex, elem: T) -> ListIndex {
+        self.assert_used(index);
+        let this = self.new_node(elem);
+        self.linkin_before(this, index);
+        this
+    }
+    /// Add a new node as the sibling just after the node at the index.
+    ///
+    /// Panics if the index is not in use.
+    pub fn insert_sibling_after(&mut self, index: ListIndex, elem: T) -> ListIndex {
+        self.assert_used(index);
+        let this = self.new_node(elem);
+        self.linkin_after(this, index);
+        this
+    }
+    /// Detach the subtree at the index from its parent, making it the last
+    /// root of the tree.
+    ///
+    /// Returns false if the index is not in use.
+    pub fn detach(&mut self, index: ListIndex) -> bool {
+        if !self.is_index_used(index) {
+            return false;
+        }
+        self.linkout(index);
+        self.linkin_last(ListIndex::new(), index);
+        true
+    }
+    /// Attach the subtree at the index as the last child of the parent,
+    /// detaching it from where it is now.
+    ///
+    /// Returns false if either index is not in use, or if the parent is in
+    /// the subtree itself.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexTree;
+    /// # let mut tree = IndexTree::new();
+    /// let root = tree.new_root(0);
+    /// let child = tree.append_child(root, 1);
+    /// let grandchild = tree.append_child(child, 2);
+    /// tree.detach(child);
+    /// assert!(tree.parent(child).is_none());
+    /// assert!(!tree.reattach(child, grandchild));
+    /// assert!(tree.reattach(child, root));
+    /// assert_eq!(tree.parent(child), root);
+    /// ```
+    pub fn reattach(&mut self, index: ListIndex, parent: ListIndex) -> bool {
+        if !self.is_index_used(index)
+            || !self.is_index_used(parent)
+            || index == parent
+            || self.ancestors(parent).any(|ancestor| ancestor == index)
+        {
+            return false;
+        }
+        self.linkout(index);
+        self.linkin_last(parent, index);
+        true
+    }
+    /// Remove the subtree at the index and return the data of its top node.
+    ///
+    /// The data of all the descendants is dropped.
+    pub fn remove(&mut self, index: ListIndex) -> Option<T> {
+        if !self.is_index_used(index) {
+            return None;
+        }
+        self.linkout(index);
+        let below: Vec<ListIndex> = self.preorder(index).skip(1).collect();
+        for this in below {
+            self.free_node(this);
+        }
+        self.free_node(index)
+    }
+    /// Remove all the nodes, making the tree empty.
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+    /// Create an iterator over the indexes of the roots.
+    #[inline]
+    pub fn roots(&self) -> TreeSiblings<'_, T> {
+        TreeSiblings { tree: self, next: self.roots.head }
+    }
+    /// Create an iterator over the indexes of the children of the node.
+    #[inline]
+    pub fn children(&self, index: ListIndex) -> TreeSiblings<'_, T> {
+        TreeSiblings { tree: self, next: self.first_child(index) }
+    }
+    /// Create an iterator over the indexes of the ancestors of the node,
+    /// from its parent up to its root.
+    #[inline]
+    pub fn ancestors(&self, index: ListIndex) -> TreeAncestors<'_, T> {
+        TreeAncestors { tree: self, next: self.parent(index) }
+    }
+    /// Create an iterator over the indexes of the subtree at the index, each
+    /// node before its children.
+    #[inline]
+    pub fn preorder(&self, index: ListIndex) -> TreePreorder<'_, T> {
+        let next = if self.is_index_used(index) { index } else { ListIndex::new() };
+        TreePreorder { tree: self, top: index, next }
+    }
+    /// Create an iterator over the indexes of the subtree at the index, each
+    /// node after its children.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::IndexTree;
+    /// # let mut tree = IndexTree::new();
+    /// let root = tree.new_root('a');
+    /// let b = tree.append_child(root, 'b');
+    /// tree.append_child(b, 'c');
+    /// tree.append_child(root, 'd');
+    /// let order: String = tree.postorder(root).map(|index| tree[index]).collect();
+    /// assert_eq!(order, "cbda");
+    /// let order: String = tree.breadth_first(root).map(|index| tree[index]).collect();
+    /// assert_eq!(order, "abdc");
+    /// ```
+    #[inline]
+    pub fn postorder(&self, index: ListIndex) -> TreePostorder<'_, T> {
+        let next = if self.is_index_used(index) { self.deepest_first(index) } else { ListIndex::new() };
+        TreePostorder { tree: self, top: index, next }
+    }
+    /// Create an iterator over the indexes of the subtree at the index, level
+    /// by level.
+    #[inline]
+    pub fn breadth_first(&self, index: ListIndex) -> TreeBreadthFirst<'_, T> {
+        let queue = self.is_index_used(index).then_some(index).into_iter().collect();
+        TreeBreadthFirst { tree: self, queue }
+    }
+
+    #[inline]
+    fn assert_used(&self, index: ListIndex) {
+        assert!(self.is_index_used(index), "index {} is not in use", index);
+    }
+    #[inline]
+    fn used_node(&self, index: ListIndex) -> Option<&ListNode> {
+        self.is_index_used(index).then(|| &self.nodes[index.get().unwrap()])
+    }
+    #[inline]
+    fn used_links(&self, index: ListIndex) -> Option<&TreeLinks> {
+        self.is_index_used(index).then(|| &self.links[index.get().unwrap()])
+    }
+    // the list of siblings that the children of the parent are in, and the
+    // nodes it is linked through
+    #[inline]
+    fn siblings_mut(&mut self, parent: ListIndex) -> (&mut ListEnds, &mut [ListNode]) {
+        let siblings = match parent.get() {
+            Some(at) => &mut self.links[at].children,
+            None => &mut self.roots,
+        };
+        (siblings, &mut self.nodes)
+    }
+    #[inline]
+    fn set_parent(&mut self, index: ListIndex, parent: ListIndex) {
+        if let Some(at) = index.get() {
+            self.links[at].parent = parent;
+        }
+    }
+    // follow the first children down to a leaf
+    fn deepest_first(&self, index: ListIndex) -> ListIndex {
+        let mut this = index;
+        while self.first_child(this).is_some() {
+            this = self.first_child(this);
+        }
+        this
+    }
+    fn new_node(&mut self, elem: T) -> ListIndex {
+        self.size += 1;
+        let reuse = self.free.head;
+        if let Some(at) = reuse.get() {
+            self.free.unlink(&mut self.nodes, reuse);
+            self.elems[at] = Some(elem);
+            self.links[at] = TreeLinks::default();
+            return reuse;
+        }
+        self.elems.push(Some(elem));
+        self.nodes.push(ListNode::new());
+        self.links.push(TreeLinks::default());
+        ListIndex::from(self.elems.len() - 1)
+    }
+    // give the slot back to the free chain, without unlinking its children
+    fn free_node(&mut self, this: ListIndex) -> Option<T> {
+        let elem = self.elems[this.get()?].take();
+        self.size -= 1;
+        self.links[this.get()?] = TreeLinks::default();
+        // the sibling links of a descendant are still set
+        self.nodes[this.get()?] = ListNode::new();
+        self.free.link_last(&mut self.nodes, this);
+        elem
+    }
+    fn linkin_last(&mut self, parent: ListIndex, this: ListIndex) {
+        let (siblings, nodes) = self.siblings_mut(parent);
+        siblings.link_last(nodes, this);
+        self.set_parent(this, parent);
+    }
+    // prev? >< that => prev? >< this >< that
+    fn linkin_before(&mut self, this: ListIndex, that: ListIndex) {
+        let parent = self.parent(that);
+        let (siblings, nodes) = self.siblings_mut(parent);
+        siblings.link_before(nodes, this, that);
+        self.set_parent(this, parent);
+    }
+    // that >< next? => that >< this >< next?
+    fn linkin_after(&mut self, this: ListIndex, that: ListIndex) {
+        let parent = self.parent(that);
+        let (siblings, nodes) = self.siblings_mut(parent);
+        siblings.link_after(nodes, this, that);
+        self.set_parent(this, parent);
+    }
+    // prev >< this >< next => prev >< next, among the siblings
+    fn linkout(&mut self, this: ListIndex) {
+        let parent = self.parent(this);
+        let (siblings, nodes) = self.siblings_mut(parent);
+        siblings.unlink(nodes, this);
+        self.set_parent(this, ListIndex::new());
+    }
+}
+
+impl<T> Index<ListIndex> for IndexTree<T> {
+    type Output = T;
+    fn index(&self, index: ListIndex) -> &Self::Output {
+        self.get(index).unwrap_or_else(|| panic!("index {} is not in use", index))
+    }
+}
+
+impl<T> IndexMut<ListIndex> for IndexTree<T> {
+    fn index_mut(&mut self, index: ListIndex) -> &mut Self::Output {
+        self.get_mut(index).unwrap_or_else(|| panic!("index {} is not in use", index))
+    }
+}
+
+impl<T> fmt::Debug for IndexTree<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.roots().map(|root| DebugNode(self, root))).finish()
+    }
+}
+
+struct DebugNode<'a, T>(&'a IndexTree<T>, ListIndex);
+
+impl<T> fmt::Debug for DebugNode<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let DebugNode(tree, index) = *self;
+        if tree.first_child(index).is_none() {
+            return tree[index].fmt(f);
+        }
+        f.debug_tuple("")
+            .field(&tree[index])
+            .field(&DebugChildren(tree, index))
+            .finish()
+    }
+}
+
+struct DebugChildren<'a, T>(&'a IndexTree<T>, ListIndex);
+
+impl<T> fmt::Debug for DebugChildren<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let DebugChildren(tree, index) = *self;
+        f.debug_list().entries(tree.children(index).map(|child| DebugNode(tree, child))).finish()
+    }
+}
+
+/// An iterator over the indexes of a run of siblings in an `IndexTree`.
+pub struct TreeSiblings<'a, T> {
+    tree: &'a IndexTree<T>,
+    next: ListIndex,
+}
+
+impl<T> Iterator for TreeSiblings<'_, T> {
+    type Item = ListIndex;
+    fn next(&mut self) -> Option<Self::Item> {
+        let this = self.next;
+        if this.is_none() {
+            return None;
+        }
+        self.next = self.tree.next_sibling(this);
+        Some(this)
+    }
+}
+impl<T> FusedIterator for TreeSiblings<'_, T> {}
+
+/// An iterator over the indexes of the ancestors of a node in an `IndexTree`.
+pub struct TreeAncestors<'a, T> {
+    tree: &'a IndexTree<T>,
+    next: ListIndex,
+}
+
+impl<T> Iterator for TreeAncestors<'_, T> {
+    type Item = ListIndex;
+    fn next(&mut self) -> Option<Self::Item> {
+        let this = self.next;
+        if this.is_none() {
+            return None;
+        }
+        self.next = self.tree.parent(this);
+        Some(this)
+    }
+}
+impl<T> FusedIterator for TreeAncestors<'_, T> {}
+
+/// An iterator over the indexes of a subtree of an `IndexTree`, in pre-order.
+pub struct TreePreorder<'a, T> {
+    tree: &'a IndexTree<T>,
+    top: ListIndex,
+    next: ListIndex,
+}
+
+impl<T> Iterator for TreePreorder<'_, T> {
+    type Item = ListIndex;
+    fn next(&mut self) -> Option<Self::Item> {
+        let this = self.next;
+        if this.is_none() {
+            return None;
+        }
+        let tree = self.tree;
+        self.next = tree.first_child(this);
+        let mut up = this;
+        // climb until there is a next sibling, without leaving the subtree
+        while self.next.is_none() && up != self.top {
+            self.next = tree.next_sibling(up);
+            up = tree.parent(up);
+        }
+        Some(this)
+    }
+}
+impl<T> FusedIterator for TreePreorder<'_, T> {}
+
+/// An iterator over the indexes of a subtree of an `IndexTree`, in
+/// post-order.
+pub struct TreePostorder<'a, T> {
+    tree: &'a IndexTree<T>,
+    top: ListIndex,
+    next: ListIndex,
+}
+
+impl<T> Iterator for TreePostorder<'_, T> {
+    type Item = ListIndex;
+    fn next(&mut self) -> Option<Self::Item> {
+        let this = self.next;
+        if this.is_none() {
+            return None;
+        }
+        let tree = self.tree;
+        self.next = if this == self.top {
+            ListIndex::new()
+        } else {
+            match tree.next_sibling(this) {
+                next if next.is_some() => tree.deepest_first(next),
+                _ => tree.parent(this),
+            }
+        };
+        Some(this)
+    }
+}
+impl<T> FusedIterator for TreePostorder<'_, T> {}
+
+/// An iterator over the indexes of a subtree of an `IndexTree`, level by
+/// level.
+pub struct TreeBreadthFirst<'a, T> {
+    tree: &'a IndexTree<T>,
+    queue: VecDeque<ListIndex>,
+}
+
+impl<T> Iterator for TreeBreadthFirst<'_, T> {
+    type Item = ListIndex;
+    fn next(&mut self) -> Option<Self::Item> {
+        let this = self.queue.pop_front()?;
+        self.queue.extend(self.tree.children(this));
+        Some(this)
+    }
+}
+impl<T> FusedIterator for TreeBreadthFirst<'_, T> {}
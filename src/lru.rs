@@ -0,0 +1,275 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the LruCache type
+//!
+//! The cache keeps its entries in an `IndexList`, ordered from the least to
+//! the most recently used, and a hash map from each key to the index of its
+//! entry. Using an entry moves it to the end of the list, and the entry at
+//! the beginning is the one to evict. An evicted entry leaves its slot on the
+//! free chain, where the next entry to be inserted picks it up.
+use std::{borrow::Borrow, collections::HashMap, fmt, hash::Hash, iter::Rev};
+use crate::{listindex::ListIndex, listiter::ListIter, IndexList};
+
+// the most entries to allocate room for before they are added
+const PREALLOC_MAX: usize = 1024;
+
+/// A least recently used cache with a fixed capacity.
+pub struct LruCache<K, V> {
+    list: IndexList<(K, V)>,
+    map: HashMap<K, ListIndex>,
+    capacity: usize,
+    on_evict: Option<Box<dyn FnMut(K, V) + Send>>,
+}
+
+impl<K, V> LruCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Creates a new empty cache that holds at most `capacity` entries.
+    ///
+    /// Room for at most 1024 entries is allocated up front, the rest is
+    /// allocated as the cache fills up.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::lru::LruCache;
+    ///
+    /// let cache = LruCache::<u32, String>::new(100);
+    /// assert_eq!(cache.capacity(), 100);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            list: IndexList::with_capacity(capacity.min(PREALLOC_MAX)),
+            map: HashMap::with_capacity(capacity.min(PREALLOC_MAX)),
+            capacity,
+            on_evict: None,
+        }
+    }
+    /// Returns the maximum number of entries in the cache.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Returns the number of entries in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+    /// Returns true when the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+    /// Returns true if the key is in the cache, without marking it as used.
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+    /// Set a callback that is called with every entry that is evicted to make
+    /// room for a new one, or because the capacity was reduced.
+    ///
+    /// Entries that are removed explicitly, by `pop_lru`, `remove` or `clear`,
+    /// are not passed to the callback.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::lru::LruCache;
+    /// # use std::sync::mpsc;
+    /// let (log, evicted) = mpsc::channel();
+    /// let mut cache = LruCache::new(2);
+    /// cache.set_eviction_callback(move |key, _value| log.send(key).unwrap());
+    /// cache.put(1, "one");
+    /// cache.put(2, "two");
+    /// cache.get(&1);
+    /// cache.put(3, "three");
+    /// assert_eq!(evicted.try_iter().collect::<Vec<_>>(), vec![2]);
+    /// ```
+    pub fn set_eviction_callback<F>(&mut self, f: F)
+    where
+        F: FnMut(K, V) + Send + 'static,
+    {
+        self.on_evict = Some(Box::new(f));
+    }
+    /// Remove the eviction callback, if any.
+    #[inline]
+    pub fn clear_eviction_callback(&mut self) {
+        self.on_evict = None;
+    }
+    /// Get a reference to the value of the key and mark it as the most
+    /// recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.list.move_to_last(index);
+        self.list.get(index).map(|(_, value)| value)
+    }
+    /// Get a mutable reference to the value of the key and mark it as the
+    /// most recently used.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.list.move_to_last(index);
+        self.list.get_mut(index).map(|(_, value)| value)
+    }
+    /// Get a reference to the value of the key, without marking it as used.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.list.get(index).map(|(_, value)| value)
+    }
+    /// Get the least recently used entry, the next one to be evicted.
+    #[inline]
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.list.get(self.list.first_index()).map(|(key, value)| (key, value))
+    }
+    /// Insert the value for the key and mark it as the most recently used.
+    ///
+    /// If the key was already in the cache then its old value is returned.
+    /// Otherwise, if the cache is full, the least recently used entry is
+    /// evicted first.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::lru::LruCache;
+    /// let mut cache = LruCache::new(2);
+    /// assert_eq!(cache.put("a", 1), None);
+    /// assert_eq!(cache.put("b", 2), None);
+    /// assert_eq!(cache.put("a", 3), Some(1));
+    /// cache.put("c", 4);
+    /// assert_eq!(cache.peek("b"), None);
+    /// assert_eq!(cache.peek("a"), Some(&3));
+    /// ```
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&index) = self.map.get(&key) {
+            self.list.move_to_last(index);
+            return self.list.get_mut(index).map(|(_, old)| std::mem::replace(old, value));
+        }
+        if self.capacity == 0 {
+            self.evict(key, value);
+            return None;
+        }
+        self.shrink_to(self.capacity - 1);
+        let index = self.list.insert_last((key.clone(), value));
+        self.map.insert(key, index);
+        None
+    }
+    /// Remove the least recently used entry and return it.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.remove_first()?;
+        self.map.remove(&key);
+        Some((key, value))
+    }
+    /// Remove the key from the cache and return its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.map.remove(key)?;
+        self.list.remove(index).map(|(_, value)| value)
+    }
+    /// Change the capacity of the cache, evicting the least recently used
+    /// entries that no longer fit.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::lru::LruCache;
+    /// let mut cache = LruCache::new(4);
+    /// cache.extend((0..4).map(|n| (n, n * n)));
+    /// cache.resize(2);
+    /// assert_eq!(cache.iter().collect::<Vec<_>>(), vec![(&3, &9), (&2, &4)]);
+    /// ```
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.shrink_to(capacity);
+    }
+    /// Remove all the entries, keeping the capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.map.clear();
+    }
+    /// Create an iterator over the entries, from the most to the least
+    /// recently used, without marking them as used.
+    #[inline]
+    pub fn iter(&self) -> LruIter<'_, K, V> {
+        LruIter { iter: self.list.iter().rev() }
+    }
+
+    fn shrink_to(&mut self, len: usize) {
+        while self.list.len() > len {
+            if let Some((key, value)) = self.pop_lru() {
+                self.evict(key, value);
+            }
+        }
+    }
+    fn evict(&mut self, key: K, value: V) {
+        if let Some(f) = self.on_evict.as_mut() {
+            f(key, value);
+        }
+    }
+}
+
+impl<K, V> Extend<(K, V)> for LruCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.put(key, value);
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for LruCache<K, V>
+where
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the entries of an `LruCache`, from the most to the least
+/// recently used.
+pub struct LruIter<'a, K, V> {
+    iter: Rev<ListIter<'a, (K, V)>>,
+}
+
+impl<'a, K, V> Iterator for LruIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, value)| (key, value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for LruIter<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for LruIter<'_, K, V> {}
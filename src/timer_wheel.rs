@@ -0,0 +1,283 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! The definition of the TimerWheel type
+//!
+//! The wheel has a number of levels with 64 buckets each, where a bucket on
+//! level `n` covers `64^n` ticks. A timer goes into the bucket of the highest
+//! base-64 digit where its deadline differs from the current time, so the
+//! buckets on each level are visited in order as time goes by. When the time
+//! reaches a bucket above the first level, its timers cascade down into the
+//! levels below, and when it reaches a bucket on the first level its timers
+//! expire.
+//!
+//! All the buckets are lists in one `IndexListArena`. Moving a timer from one
+//! bucket to another keeps its index, which is what the `TimerHandle` holds,
+//! so a timer can be cancelled or rescheduled in constant time wherever it
+//! is. The wheel has no clock of its own, it only knows the time passed to
+//! `advance`.
+use std::fmt;
+use crate::{listarena::{IndexListArena, ListId}, listindex::ListIndex};
+
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+// enough levels to cover all 64 bits of a deadline
+const LEVELS: usize = 11;
+// the bucket for timers that were already due when scheduled
+const DUE: usize = LEVELS * SLOTS;
+
+/// A handle to a scheduled timer, used to cancel or reschedule it.
+///
+/// *NOTE* that the handle is only valid until the timer expires or is
+/// cancelled, after which it may refer to another timer.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TimerHandle(ListIndex);
+
+impl TimerHandle {
+    /// Returns the index of the timer in the underlying arena.
+    #[inline]
+    pub fn index(&self) -> ListIndex {
+        self.0
+    }
+}
+
+struct TimerEntry<T> {
+    deadline: u64,
+    bucket: usize,
+    data: T,
+}
+
+/// A hierarchical timing wheel, driven by a clock supplied by the caller.
+pub struct TimerWheel<T> {
+    arena: IndexListArena<TimerEntry<T>>,
+    buckets: Vec<ListId>,
+    occupied: [u64; LEVELS],
+    now: u64,
+}
+
+impl<T> Default for TimerWheel<T> {
+    fn default() -> Self {
+        TimerWheel::new(0)
+    }
+}
+
+impl<T> TimerWheel<T> {
+    /// Creates a new empty wheel, with the current time set to `now`.
+    ///
+    /// Example:
+    /// ```rust
+    /// use index_list::TimerWheel;
+    ///
+    /// let wheel = TimerWheel::<&str>::new(1000);
+    /// assert_eq!(wheel.now(), 1000);
+    /// ```
+    pub fn new(now: u64) -> Self {
+        let mut arena = IndexListArena::new();
+        let buckets = (0..=DUE).map(|_| arena.new_list()).collect();
+        TimerWheel { arena, buckets, occupied: [0; LEVELS], now }
+    }
+    /// Returns the current time, as of the last call to `advance`.
+    #[inline]
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+    /// Returns the number of scheduled timers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.arena.len()
+    }
+    /// Returns true when no timers are scheduled.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.arena.is_empty()
+    }
+    /// Returns the deadline of the timer, or `None` if it is not scheduled.
+    #[inline]
+    pub fn deadline(&self, handle: TimerHandle) -> Option<u64> {
+        self.arena.get(handle.0).map(|entry| entry.deadline)
+    }
+    /// Get a reference to the data of the timer, or `None`.
+    #[inline]
+    pub fn get(&self, handle: TimerHandle) -> Option<&T> {
+        self.arena.get(handle.0).map(|entry| &entry.data)
+    }
+    /// Get a mutable reference to the data of the timer, or `None`.
+    #[inline]
+    pub fn get_mut(&mut self, handle: TimerHandle) -> Option<&mut T> {
+        self.arena.get_mut(handle.0).map(|entry| &mut entry.data)
+    }
+    /// Schedule a timer to expire at the deadline.
+    ///
+    /// A deadline that is not after the current time expires on the next
+    /// call to `advance`.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::TimerWheel;
+    /// let mut wheel = TimerWheel::new(0);
+    /// wheel.schedule(300, "late");
+    /// wheel.schedule(5, "soon");
+    /// let cancelled = wheel.schedule(70, "never");
+    /// assert_eq!(wheel.cancel(cancelled), Some("never"));
+    /// let expired: Vec<_> = wheel.advance(1000).into_iter().map(|(_, data)| data).collect();
+    /// assert_eq!(expired, vec!["soon", "late"]);
+    /// ```
+    pub fn schedule(&mut self, deadline: u64, data: T) -> TimerHandle {
+        let bucket = self.bucket_for(deadline);
+        let entry = TimerEntry { deadline, bucket, data };
+        let index = self.arena.insert_last(self.buckets[bucket], entry);
+        self.mark(bucket);
+        TimerHandle(index)
+    }
+    /// Schedule a timer to expire `delay` ticks after the current time.
+    #[inline]
+    pub fn schedule_after(&mut self, delay: u64, data: T) -> TimerHandle {
+        self.schedule(self.now.saturating_add(delay), data)
+    }
+    /// Cancel the timer and return its data, or `None` if it is not
+    /// scheduled.
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        let bucket = self.arena.get(handle.0)?.bucket;
+        let entry = self.arena.remove(handle.0)?;
+        self.unmark(bucket);
+        Some(entry.data)
+    }
+    /// Move the timer to a new deadline, keeping its handle.
+    ///
+    /// Returns false if the timer is not scheduled.
+    pub fn reschedule(&mut self, handle: TimerHandle, deadline: u64) -> bool {
+        let old = match self.arena.get(handle.0) {
+            Some(entry) => entry.bucket,
+            None => return false,
+        };
+        self.relink(handle.0, deadline);
+        self.unmark(old);
+        true
+    }
+    /// Move the time forward to `now` and return the timers that expire by
+    /// then, in the order of their deadlines.
+    ///
+    /// Timers with the same deadline are returned in the order they were
+    /// scheduled. Time never moves backwards, but timers that were already
+    /// due when scheduled are returned even if `now` is in the past.
+    ///
+    /// Example:
+    /// ```rust
+    /// # use index_list::TimerWheel;
+    /// let mut wheel = TimerWheel::new(0);
+    /// let handle = wheel.schedule(10_000, 'a');
+    /// wheel.schedule(4_000, 'b');
+    /// assert!(wheel.advance(3_999).is_empty());
+    /// assert_eq!(wheel.advance(5_000).len(), 1);
+    /// wheel.reschedule(handle, 6_000);
+    /// assert_eq!(wheel.advance(6_000), vec![(handle, 'a')]);
+    /// assert!(wheel.is_empty());
+    /// ```
+    pub fn advance(&mut self, now: u64) -> Vec<(TimerHandle, T)> {
+        let mut expired = Vec::new();
+        self.drain(DUE, &mut expired);
+        expired.sort_by_key(|(_, deadline, _)| *deadline);
+        while let Some((bucket, time)) = self.next_bucket() {
+            if time > now {
+                break;
+            }
+            self.now = time;
+            if bucket < SLOTS {
+                self.drain(bucket, &mut expired);
+            } else {
+                self.cascade(bucket, &mut expired);
+            }
+        }
+        self.now = self.now.max(now);
+        expired.into_iter().map(|(handle, _, data)| (handle, data)).collect()
+    }
+
+    // the bucket for the deadline, relative to the current time
+    fn bucket_for(&self, deadline: u64) -> usize {
+        if deadline <= self.now {
+            return DUE;
+        }
+        let level = (63 - (deadline ^ self.now).leading_zeros()) / SLOT_BITS;
+        let slot = (deadline >> (level * SLOT_BITS)) as usize & (SLOTS - 1);
+        level as usize * SLOTS + slot
+    }
+    #[inline]
+    fn mark(&mut self, bucket: usize) {
+        if bucket < DUE {
+            self.occupied[bucket / SLOTS] |= 1 << (bucket % SLOTS);
+        }
+    }
+    #[inline]
+    fn unmark(&mut self, bucket: usize) {
+        if bucket < DUE && self.arena.list_len(self.buckets[bucket]) == 0 {
+            self.occupied[bucket / SLOTS] &= !(1 << (bucket % SLOTS));
+        }
+    }
+    fn relink(&mut self, index: ListIndex, deadline: u64) {
+        let bucket = self.bucket_for(deadline);
+        self.arena.move_to_last(index, self.buckets[bucket]);
+        if let Some(entry) = self.arena.get_mut(index) {
+            entry.deadline = deadline;
+            entry.bucket = bucket;
+        }
+        self.mark(bucket);
+    }
+    // the first bucket to be visited, and the time it is reached
+    fn next_bucket(&self) -> Option<(usize, u64)> {
+        // the buckets ahead on a lower level are always reached first
+        for level in 0..LEVELS {
+            let shift = level as u32 * SLOT_BITS;
+            let digit = (self.now >> shift) as usize & (SLOTS - 1);
+            let ahead = (self.occupied[level] >> digit) >> 1;
+            if ahead == 0 {
+                continue;
+            }
+            let slot = digit + 1 + ahead.trailing_zeros() as usize;
+            let above = shift + SLOT_BITS;
+            let base = if above >= 64 { 0 } else { self.now >> above << above };
+            return Some((level * SLOTS + slot, base | (slot as u64) << shift));
+        }
+        None
+    }
+    // move the timers of a bucket above the first level to lower levels
+    fn cascade(&mut self, bucket: usize, expired: &mut Vec<(TimerHandle, u64, T)>) {
+        let list = self.buckets[bucket];
+        while self.arena.first_index(list).is_some() {
+            let index = self.arena.first_index(list);
+            let deadline = self.arena[index].deadline;
+            self.relink(index, deadline);
+        }
+        self.unmark(bucket);
+        self.drain(DUE, expired);
+    }
+    fn drain(&mut self, bucket: usize, expired: &mut Vec<(TimerHandle, u64, T)>) {
+        let list = self.buckets[bucket];
+        loop {
+            let index = self.arena.first_index(list);
+            match self.arena.remove(index) {
+                Some(entry) => expired.push((TimerHandle(index), entry.deadline, entry.data)),
+                None => break,
+            }
+        }
+        self.unmark(bucket);
+    }
+}
+
+impl<T> fmt::Debug for TimerWheel<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut timers = f.debug_map();
+        // the due timers first, then roughly in the order they will expire
+        let due = self.buckets[DUE];
+        for &list in Some(&due).into_iter().chain(&self.buckets[..DUE]) {
+            for entry in self.arena.iter(list) {
+                timers.entry(&entry.deadline, &entry.data);
+            }
+        }
+        timers.finish()
+    }
+}
@@ -3,9 +3,15 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
-use index_list::{IndexList, ListIndex};
+use index_list::{
+    IndexList, IndexListArena, IndexListMap, IndexListSet, IndexTree, LabeledIndexList, LfuCache,
+    ListChain, ListEntry, ListIndex, ListPosition, LruCache, MapEntry, MultiIndexList,
+    RankedIndexList, ReusePolicy, TimerHandle, TimerWheel, TrimPolicy,
+};
+use std::cmp::Ordering;
 use std::mem::size_of;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use rand::{Rng, seq::SliceRandom};
 
 fn debug_print_indexes(list: &IndexList<u64>) {
@@ -28,6 +34,7 @@ fn get_raw_index(index: &ListIndex) -> u32 {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_instantiate() {
     let mut list = IndexList::<u64>::new();
     let null = ListIndex::from(None);
@@ -63,6 +70,7 @@ fn test_instantiate() {
     list.move_to_last(ListIndex::new())
 }
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn basic_insert_remove() {
     let mut list = IndexList::<u64>::new();
     let count = 9;
@@ -98,6 +106,7 @@ fn test_while_get_mut() {
     assert_eq!(list.to_string(), "[a >< b >< c]");
 }
 #[test]
+#[allow(clippy::option_map_unit_fn)]
 fn test_append() {
     let mut list = IndexList::from(&mut vec!["A", "B", "C"]);
     let mut other = IndexList::from(&mut vec!["D", "E", "F"]);
@@ -140,6 +149,7 @@ fn test_trim_swap() {
     }
 }
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_single_element() {
     let mut list = IndexList::<u64>::new();
     for num in 0..8 {
@@ -162,6 +172,7 @@ fn test_single_element() {
     assert_eq!(list.len(), 0);
 }
 #[test]
+#[allow(clippy::unnecessary_cast)]
 fn insert_remove_variants() {
     let count = 256;
     let mut rng = rand::thread_rng();
@@ -219,3 +230,1094 @@ fn insert_remove_variants() {
         assert_eq!(list.capacity(), 0);
     }
 }
+#[test]
+fn test_index_and_entry() {
+    let mut list = IndexList::from(&mut vec![1, 2, 3]);
+    let first = list.first_index();
+    let last = list.last_index();
+    list[first] += 10;
+    assert_eq!(list[first], 11);
+    assert_eq!(list.replace(last, 30), Some(3));
+    assert_eq!(list.take(first), Some(11));
+    assert_eq!(list.to_string(), "[0 >< 2 >< 30]");
+    match list.entry(last) {
+        ListEntry::Occupied(mut entry) => {
+            assert_eq!(entry.replace(3), 30);
+            entry.insert_before(4);
+            entry.move_to_first();
+        }
+        ListEntry::Vacant(_) => unreachable!(),
+    }
+    assert_eq!(list.to_string(), "[3 >< 0 >< 2 >< 4]");
+    let removed = list.remove(first);
+    assert_eq!(removed, Some(0));
+    assert!(matches!(list.entry(first), ListEntry::Vacant(_)));
+    *list.entry(first).or_insert_last(5) += 1;
+    assert_eq!(list.to_string(), "[3 >< 2 >< 4 >< 6]");
+    if let ListEntry::Occupied(entry) = list.entry(last) {
+        assert_eq!(entry.remove(), 3);
+    }
+    assert_eq!(list.len(), 3);
+}
+#[test]
+#[should_panic]
+fn test_index_unused_panics() {
+    let mut list = IndexList::from(&mut vec![1, 2, 3]);
+    let index = list.first_index();
+    list.remove(index);
+    let _ = list[index];
+}
+#[test]
+#[should_panic]
+fn test_insert_many_unused_panics() {
+    let mut list = IndexList::from(&mut vec![1, 2, 3]);
+    let index = list.first_index();
+    list.remove(index);
+    list.insert_many_after(index, vec![4, 5]);
+}
+#[test]
+fn test_remove_unused() {
+    let mut list = IndexList::from(&mut vec![1, 2, 3]);
+    let index = list.first_index();
+    assert_eq!(list.remove(index), Some(1));
+    assert_eq!(list.remove(index), None);
+    assert_eq!(list.len(), 2);
+    assert_eq!(list.remove(ListIndex::from(10u32)), None);
+    assert_eq!(list.len(), 2);
+}
+#[test]
+fn test_get_many_mut() {
+    let mut list: IndexList<u64> = (0..8).collect();
+    let indexes: Vec<ListIndex> = (0..8)
+        .map(|n| list.move_index(list.first_index(), n))
+        .collect();
+    let [a, b, c] = list.get_many_mut([indexes[7], indexes[0], indexes[3]])
+        .unwrap();
+    assert_eq!((*a, *b, *c), (7, 0, 3));
+    *a += 10;
+    *c += 10;
+    assert_eq!(list.get_last(), Some(&17));
+    let (x, y) = list.get_pair_mut(indexes[1], indexes[2]).unwrap();
+    std::mem::swap(x, y);
+    assert_eq!(list.to_string(), "[0 >< 2 >< 1 >< 13 >< 4 >< 5 >< 6 >< 17]");
+    assert!(list.get_pair_mut(indexes[4], indexes[4]).is_none());
+    list.remove(indexes[5]);
+    assert!(list.get_many_mut([indexes[4], indexes[5]]).is_none());
+    assert!(list.get_many_mut([indexes[4], ListIndex::new()]).is_none());
+    assert!(list.get_many_mut::<0>([]).is_some());
+}
+#[test]
+fn test_insert_with() {
+    let mut list = IndexList::<(ListIndex, u64)>::new();
+    let mid = list.insert_last_with(|me| (me, 2));
+    let gone = list.insert_first((ListIndex::new(), 0));
+    list.remove(gone);
+    let first = list.insert_first_with(|me| (me, 1));
+    let before = list.insert_before_with(mid, |me| (me, 3));
+    let after = list.insert_after_with(mid, |me| (me, 4));
+    let last = list.insert_after_with(ListIndex::new(), |me| (me, 5));
+    let order: Vec<ListIndex> = list.iter().map(|(ndx, _)| *ndx).collect();
+    assert_eq!(order, vec![first, before, mid, after, last]);
+    for (ndx, _) in list.iter() {
+        assert_eq!(list.get(*ndx).unwrap().0, *ndx);
+    }
+    assert_eq!(list.len(), 5);
+    assert_eq!(list.capacity(), 5);
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        list.insert_last_with(|_| panic!("no element"));
+    }));
+    assert!(panicked.is_err());
+    assert_eq!(list.len(), 5);
+    assert_eq!(list.validate(), Ok(()));
+    let reused = list.insert_last_with(|me| (me, 6));
+    assert_eq!(list.capacity(), 6);
+    assert_eq!(list.get_last(), Some(&(reused, 6)));
+}
+#[test]
+fn test_reserve_index() {
+    let mut list = IndexList::from(&mut vec![2, 4]);
+    let one = list.reserve_index();
+    let three = list.reserve_index();
+    let five = list.reserve_index();
+    let gone = list.reserve_index();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list.capacity(), 6);
+    assert!(list.is_index_reserved(one));
+    assert!(!list.is_index_used(one));
+    assert_eq!(list.to_string(), "[2 >< 4]");
+    let four = list.last_index();
+    assert_eq!(list.fill_reserved(one, 1, ListPosition::First), Ok(()));
+    assert_eq!(list.fill_reserved(five, 5, ListPosition::After(four)), Ok(()));
+    assert_eq!(list.fill_reserved(three, 3, ListPosition::Before(four)), Ok(()));
+    assert_eq!(list.fill_reserved(three, 6, ListPosition::Last), Err(6));
+    assert!(!list.is_index_reserved(three));
+    assert_eq!(list.to_string(), "[1 >< 2 >< 3 >< 4 >< 5]");
+    assert_eq!(list.len(), 5);
+    // the abandoned reservation is the only unused slot and is kept by trim
+    list.trim_safe();
+    assert_eq!(list.capacity(), 6);
+    list.release_reserved(gone);
+    assert!(!list.is_index_reserved(gone));
+    list.trim_safe();
+    assert_eq!(list.capacity(), 5);
+    // trim_swap releases any outstanding reservations
+    let kept = list.reserve_index();
+    list.remove_first();
+    assert_eq!(list.fill_reserved(kept, 0, ListPosition::After(one)), Err(0));
+    list.trim_swap();
+    assert_eq!(list.capacity(), 4);
+    assert_eq!(list.to_string(), "[2 >< 3 >< 4 >< 5]");
+}
+#[test]
+fn test_insert_many() {
+    let mut list = IndexList::<u64>::new();
+    let middle = list.insert_many_before(ListIndex::new(), 4..6);
+    assert_eq!(list.to_string(), "[4 >< 5]");
+    let head = list.insert_many_before(middle[0], 1..4);
+    let tail = list.extend_returning_indexes(8..10);
+    list.insert_many_after(middle[1], 6..8);
+    list.insert_many_after(tail[1], 10..11);
+    list.insert_many_before(ListIndex::new(), 0..1);
+    list.insert_many_after(head[0], std::iter::empty());
+    assert_eq!(list.len(), 11);
+    assert!(list.iter().copied().eq(0..11));
+    for (indexes, first) in [(head, 1), (middle, 4), (tail, 8)] {
+        for (n, ndx) in indexes.iter().enumerate() {
+            assert_eq!(list.get(*ndx), Some(&(first + n as u64)));
+        }
+    }
+    list.remove_first();
+    list.remove_last();
+    let reused = list.extend_returning_indexes(vec![11, 12]);
+    assert_eq!(list.capacity(), 11);
+    assert_eq!(list.get_last(), Some(&12));
+    assert_eq!(list.prev_index(list.last_index()), reused[0]);
+}
+#[test]
+fn test_reserve_and_shrink() {
+    let mut list = IndexList::<u64>::with_capacity(16);
+    assert_eq!(list.capacity(), 0);
+    list.extend(0..8);
+    list.reserve(8);
+    list.reserve_exact(8);
+    assert!(list.try_reserve(8).is_ok());
+    assert!(list.try_reserve(usize::MAX).is_err());
+    assert_eq!(list.capacity(), 8);
+    assert_eq!(list.len(), 8);
+    list.remove_last();
+    list.remove_first();
+    list.shrink_to_fit();
+    assert_eq!(list.capacity(), 7);
+    assert!(list.iter().copied().eq(1..7));
+    list.extend(7..9);
+    assert_eq!(list.capacity(), 8);
+}
+#[test]
+fn test_reuse_policy() {
+    let removal = [2, 6, 0, 4];
+    let expect = [
+        (ReusePolicy::Fifo, [2, 6, 0, 4]),
+        (ReusePolicy::Lifo, [4, 0, 6, 2]),
+        (ReusePolicy::LowestFirst, [0, 2, 4, 6]),
+    ];
+    for (policy, order) in expect {
+        let mut list = IndexList::<u64>::with_reuse_policy(policy);
+        assert_eq!(list.reuse_policy(), policy);
+        list.extend(0..8);
+        for n in removal {
+            list.remove(ListIndex::from(n as usize));
+        }
+        for n in order {
+            assert_eq!(list.insert_last(n), ListIndex::from(n as usize));
+        }
+        assert_eq!(list.capacity(), 8);
+    }
+    // lowest-first keeps the free indexes at the end so they can be trimmed
+    let mut list = IndexList::<u64>::new();
+    list.extend(0..8);
+    list.remove(ListIndex::from(1u32));
+    list.remove(ListIndex::from(7u32));
+    list.remove(ListIndex::from(5u32));
+    list.set_reuse_policy(ReusePolicy::LowestFirst);
+    list.remove(ListIndex::from(3u32));
+    list.trim_safe();
+    assert_eq!(list.capacity(), 7);
+    let reused: Vec<ListIndex> = (0..2).map(|n| list.insert_first(n)).collect();
+    assert_eq!(reused, vec![ListIndex::from(1u32), ListIndex::from(3u32)]);
+    list.remove(ListIndex::from(6u32));
+    list.trim_safe();
+    assert_eq!(list.capacity(), 5);
+    assert_eq!(list.insert_last(8), ListIndex::from(5u32));
+    assert_eq!(list.len(), list.capacity());
+}
+#[test]
+fn test_trim_policy() {
+    use std::sync::{Arc, Mutex};
+    let mut list = IndexList::<u64>::new();
+    list.set_trim_policy(TrimPolicy::Safe { min_load: 0.5 });
+    assert_eq!(list.trim_policy(), TrimPolicy::Safe { min_load: 0.5 });
+    list.extend(0..64);
+    // removing from the front leaves the free indexes where trim_safe can't
+    // reach them
+    (0..40).for_each(|_| { list.remove_first(); });
+    assert_eq!(list.capacity(), 64);
+    assert_eq!(list.trim_stats().runs, 0);
+    (0..20).for_each(|_| { list.remove_last(); });
+    assert_eq!(list.capacity(), 44);
+    assert!(list.trim_stats().runs > 0);
+    // compacting moves the elements and reports it
+    let moved = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&moved);
+    list.set_relocation_callback(move |old, new| log.lock().unwrap().push((old, new)));
+    list.set_trim_policy(TrimPolicy::Compact { min_load: 0.5 });
+    let runs = list.trim_stats().runs;
+    let before: Vec<(ListIndex, u64)> = (0..list.len() as i32)
+        .map(|n| list.move_index(list.first_index(), n))
+        .map(|ndx| (ndx, list[ndx]))
+        .collect();
+    let other = list.split(before[2].0);
+    assert_eq!(other.len(), 2);
+    assert_eq!(list.capacity(), 2);
+    assert!(list.iter().copied().eq(40..42));
+    let moved = moved.lock().unwrap();
+    assert_eq!(moved.len(), 2);
+    for (old, new) in moved.iter() {
+        let (_, value) = before.iter().find(|(ndx, _)| ndx == old).unwrap();
+        assert_eq!(list.get(*new), Some(value));
+    }
+    assert_eq!(list.trim_stats().runs, runs + 1);
+    assert!(list.trim_stats().bytes_reclaimed > 0);
+    list.set_trim_policy(TrimPolicy::Safe { min_load: 4.0 });
+    assert_eq!(list.trim_policy(), TrimPolicy::Safe { min_load: 1.0 });
+    for min_load in [0.0, -0.5, f32::NAN] {
+        list.set_trim_policy(TrimPolicy::Compact { min_load });
+        assert_eq!(list.trim_policy(), TrimPolicy::Manual);
+    }
+}
+#[test]
+fn test_stats() {
+    let list = IndexList::<u64>::new();
+    let stats = list.stats();
+    assert_eq!(stats.capacity, 0);
+    assert_eq!(stats.fragmentation, 0.0);
+    assert_eq!(stats.neighbour_distance, 0.0);
+    let mut list: IndexList<u64> = (0..10).collect();
+    list.remove(ListIndex::from(2u32));
+    list.remove(ListIndex::from(5u32));
+    list.remove(ListIndex::from(9u32));
+    list.remove(ListIndex::from(8u32));
+    list.reserve_index();
+    list.move_to_first(ListIndex::from(7u32));
+    let stats = list.stats();
+    assert_eq!(stats.used, 6);
+    assert_eq!(stats.free, 3);
+    assert_eq!(stats.reserved, 1);
+    assert_eq!(stats.capacity, 10);
+    assert_eq!(stats.trailing_free, 2);
+    assert_eq!(stats.fragmentation, 0.1);
+    assert!(stats.elems_bytes >= 10 * std::mem::size_of::<Option<u64>>());
+    assert!(stats.nodes_bytes >= 80);
+    // 7 0 1 3 4 6
+    assert_eq!(stats.neighbour_distance, 13.0 / 5.0);
+}
+#[test]
+fn test_validate() {
+    let mut rng = rand::thread_rng();
+    let mut list = IndexList::<u64>::new();
+    assert!(list.validate().is_ok());
+    let mut indexes: Vec<ListIndex> = Vec::new();
+    for n in 0..512 {
+        match rng.gen_range(0..6) {
+            0 => indexes.push(list.insert_first(n)),
+            1 => indexes.push(list.insert_last(n)),
+            2 if !indexes.is_empty() => {
+                let that = indexes[rng.gen_range(0..indexes.len())];
+                indexes.push(list.insert_after(that, n));
+            }
+            3 if !indexes.is_empty() => {
+                let this = indexes[rng.gen_range(0..indexes.len())];
+                let that = indexes[rng.gen_range(0..indexes.len())];
+                list.move_before(this, that);
+            }
+            _ if !indexes.is_empty() => {
+                let index = indexes.swap_remove(rng.gen_range(0..indexes.len()));
+                assert!(list.remove(index).is_some());
+                assert_eq!(list.remove(index), None);
+            }
+            _ => (),
+        }
+        assert_eq!(list.validate(), Ok(()));
+    }
+    list.trim_safe();
+    assert_eq!(list.validate(), Ok(()));
+    list.trim_swap();
+    assert_eq!(list.validate(), Ok(()));
+    // swapping data with an unused index breaks the list
+    list.remove_first();
+    let free = (0..list.capacity())
+        .map(ListIndex::from)
+        .find(|&ndx| !list.is_index_used(ndx))
+        .unwrap();
+    list.swap_index(list.last_index(), free);
+    assert!(list.validate().is_err());
+}
+#[test]
+fn test_layout_and_dot() {
+    let mut list: IndexList<u64> = (0..5).collect();
+    list.remove(ListIndex::from(1u32));
+    list.remove(ListIndex::from(3u32));
+    let reserved = list.reserve_index();
+    list.move_to_first(ListIndex::from(4u32));
+    let slots = list.layout();
+    assert_eq!(slots.len(), list.capacity());
+    let chains: Vec<Option<ListChain>> = slots.iter().map(|slot| slot.chain).collect();
+    assert_eq!(chains, vec![
+        Some(ListChain::Used), None, Some(ListChain::Used),
+        Some(ListChain::Free), Some(ListChain::Used),
+    ]);
+    assert_eq!(slots[1].index, reserved);
+    assert!(!slots[1].occupied && !slots[3].occupied && slots[4].occupied);
+    assert_eq!(slots[4].next, ListIndex::from(0u32));
+    assert_eq!(slots[4].prev, ListIndex::new());
+    let lines: Vec<String> = slots.iter().map(|slot| slot.to_string()).collect();
+    assert_eq!(lines, vec![
+        "1: used data 3<>5", "2: reserved empty |<>|", "3: used data |<>1",
+        "4: free empty |<>|", "5: used data 1<>|",
+    ]);
+    let dot = list.to_dot();
+    assert!(dot.contains("used_head -> n5"));
+    assert!(dot.contains("n5 -> n1"));
+    assert!(dot.contains("n1 -> n3"));
+    assert!(dot.contains("n3 -> used_tail"));
+    assert!(dot.contains("free_head -> n4"));
+    assert!(dot.contains("n2 [label=\"2 reserved\""));
+    assert!(dot.ends_with("}\n"));
+}
+#[test]
+fn test_debug_and_display() {
+    let mut list = IndexList::from(&mut vec!["A", "B", "C"]);
+    list.move_to_first(list.last_index());
+    assert_eq!(format!("{:?}", list), r#"["C", "A", "B"]"#);
+    assert_eq!(format!("{:#?}", list), "[\n    \"C\",\n    \"A\",\n    \"B\",\n]");
+    assert_eq!(format!("{:?}", IndexList::<u8>::new()), "[]");
+    assert_eq!(list.to_string(), "[C >< A >< B]");
+    assert_eq!(list.display().separator(" -> ").indexes(true).to_string(),
+               "[3:C -> 1:A -> 2:B]");
+    assert!(format!("{:?}", list.debug_layout()).contains("size: 3"));
+    let numbers = IndexList::from(&mut vec![1.5, 2.25]);
+    assert_eq!(format!("{:.1}", numbers), "[1.5 >< 2.2]");
+}
+#[test]
+fn test_iter_indexed() {
+    let mut list: IndexList<u64> = (0..6).collect();
+    list.remove(ListIndex::from(2u32));
+    list.move_to_first(ListIndex::from(4u32));
+    // 4 0 1 3 5
+    let indexes: Vec<ListIndex> = list.indexes().collect();
+    assert_eq!(indexes, [4u32, 0, 1, 3, 5].map(ListIndex::from));
+    assert!(list.indexes().rev().eq(indexes.iter().rev().copied()));
+    assert!(list.iter_indexed().all(|(ndx, elem)| list[ndx] == *elem));
+    let mut iter = list.iter_indexed();
+    assert_eq!(iter.len(), 5);
+    assert_eq!(iter.next(), Some((indexes[0], &4)));
+    assert_eq!(iter.next_back(), Some((indexes[4], &5)));
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next_back(), Some((indexes[3], &3)));
+    assert_eq!(iter.next(), Some((indexes[1], &0)));
+    assert_eq!(iter.next(), Some((indexes[2], &1)));
+    assert_eq!(iter.next_back(), None);
+    assert_eq!(iter.next(), None);
+    let mut iter = list.iter();
+    assert_eq!(iter.next(), Some(&4));
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+    assert_eq!(iter.rev().collect::<Vec<&u64>>(), vec![&5, &3, &1, &0]);
+    let mut iter = list.iter_indexed_mut();
+    assert_eq!(iter.len(), 5);
+    if let Some((_, elem)) = iter.next_back() {
+        *elem += 50;
+    }
+    for (ndx, elem) in iter {
+        *elem += 10 * ndx.to_string().parse::<u64>().unwrap();
+    }
+    assert_eq!(list.to_string(), "[54 >< 10 >< 21 >< 43 >< 55]");
+    assert_eq!(IndexList::<u64>::new().iter_indexed_mut().next(), None);
+}
+#[test]
+fn test_range_iter() {
+    let mut list: IndexList<u64> = (0..8).collect();
+    list.move_to_first(ListIndex::from(7u32));
+    list.remove(ListIndex::from(3u32));
+    // 7 0 1 2 4 5 6
+    let at = |n: u32| ListIndex::from(n);
+    assert!(list.iter_from(at(2)).copied().eq([2, 4, 5, 6]));
+    assert!(list.iter_from(at(7)).copied().eq([7, 0, 1, 2, 4, 5, 6]));
+    assert!(list.iter_back_from(at(1)).copied().eq([1, 0, 7]));
+    assert_eq!(list.iter_back_from(at(6)).count(), 7);
+    assert_eq!(list.iter_from(at(2)).size_hint(), (1, Some(7)));
+    let mut from = list.iter_from(at(1));
+    assert_eq!(from.next_back(), Some(&6));
+    assert_eq!(from.next(), Some(&1));
+    assert!(from.rev().copied().eq([5, 4, 2]));
+    assert_eq!(list.iter_from(at(3)).count(), 0);
+    assert_eq!(list.iter_back_from(ListIndex::new()).count(), 0);
+    let mut range = list.range(at(0), at(5));
+    assert_eq!(range.size_hint(), (5, Some(5)));
+    assert_eq!(range.next_back(), Some(&5));
+    assert_eq!(range.next(), Some(&0));
+    assert!(range.copied().eq([1, 2, 4]));
+    assert!(list.range(at(4), at(4)).copied().eq([4]));
+    assert_eq!(list.range(at(5), at(0)).count(), 0);
+    assert_eq!(list.range(at(3), at(5)).count(), 0);
+    list.range_mut(at(1), at(4)).rev().for_each(|elem| *elem *= 10);
+    assert_eq!(list.to_string(), "[7 >< 0 >< 10 >< 20 >< 40 >< 5 >< 6]");
+    assert_eq!(list.range_mut(at(4), at(1)).count(), 0);
+    list.iter_mut().for_each(|elem| *elem += 1);
+    assert_eq!(list.to_string(), "[8 >< 1 >< 11 >< 21 >< 41 >< 6 >< 7]");
+    let mut range = list.range_mut(at(7), at(1));
+    assert_eq!(range.len(), 3);
+    assert_eq!(range.next_back().map(|elem| *elem), Some(11));
+    assert!(range.map(|elem| *elem).eq([8, 1]));
+    assert_eq!(list.range_mut(at(0), at(3)).count(), 0);
+}
+#[test]
+fn test_iter_slots() {
+    let mut list: IndexList<u64> = (0..8).collect();
+    list.move_to_first(list.last_index());
+    list.remove(ListIndex::from(2u32));
+    list.remove(ListIndex::from(7u32));
+    list.reserve_index();
+    let slots: Vec<(ListIndex, &u64)> = list.iter_slots().collect();
+    assert_eq!(slots.len(), 6);
+    assert_eq!(slots[2], (ListIndex::from(3u32), &3));
+    assert!(list.iter_slots().all(|(ndx, elem)| list[ndx] == *elem));
+    let mut iter = list.iter_slots();
+    assert_eq!(iter.next_back(), Some((ListIndex::from(6u32), &6)));
+    assert_eq!(iter.next(), Some((ListIndex::from(0u32), &0)));
+    assert_eq!(iter.len(), 4);
+    assert!(iter.rev().map(|(_, elem)| *elem).eq([5, 4, 3, 1]));
+    let mut iter = list.iter_slots_mut();
+    if let Some((_, elem)) = iter.next_back() {
+        *elem = 60;
+    }
+    iter.for_each(|(_, elem)| *elem *= 2);
+    assert_eq!(list.to_string(), "[0 >< 2 >< 6 >< 8 >< 10 >< 60]");
+    let mut max = 0;
+    list.for_each_unordered(|elem| max = max.max(*elem));
+    assert_eq!(max, 60);
+    list.for_each_unordered_mut(|elem| *elem += 1);
+    assert_eq!(list.iter().sum::<u64>(), 92);
+}
+#[test]
+fn test_ranked_list() {
+    let mut rng = rand::thread_rng();
+    let mut list = RankedIndexList::<u64>::new();
+    let mut model: Vec<ListIndex> = Vec::new();
+    for n in 0..2000 {
+        match rng.gen_range(0..8) {
+            0 => model.insert(0, list.insert_first(n)),
+            1 => model.push(list.insert_last(n)),
+            2 | 3 => {
+                let pos = rng.gen_range(0..=model.len());
+                model.insert(pos, list.insert_at(pos, n));
+            }
+            4 if !model.is_empty() => {
+                let pos = rng.gen_range(0..model.len());
+                let index = list.insert_after(model[pos], n);
+                model.insert(pos + 1, index);
+            }
+            5 if !model.is_empty() => {
+                let pos = rng.gen_range(0..model.len());
+                let index = model.remove(pos);
+                assert_eq!(list.position_of(index), Some(pos));
+                assert!(list.remove_at(pos).is_some());
+                assert_eq!(list.position_of(index), None);
+            }
+            6 if model.len() > 1 => {
+                let from = rng.gen_range(0..model.len());
+                let to = rng.gen_range(0..model.len());
+                let index = model.remove(from);
+                let that = model[to.min(model.len() - 1)];
+                list.move_before(index, that);
+                let at = model.iter().position(|&i| i == that).unwrap();
+                model.insert(at, index);
+            }
+            _ if !model.is_empty() => {
+                let index = model.remove(0);
+                list.move_to_last(index);
+                model.push(index);
+            }
+            _ => (),
+        }
+        assert_eq!(list.len(), model.len());
+        if n % 50 == 0 {
+            assert!(list.indexes().eq(model.iter().copied()));
+            for (pos, index) in model.iter().enumerate() {
+                assert_eq!(list.nth(pos), *index);
+                assert_eq!(list.position_of(*index), Some(pos));
+            }
+        }
+    }
+    assert!(list.nth(model.len()).is_none());
+    let ranked: RankedIndexList<u64> = (0..10).collect();
+    assert_eq!(ranked.get(ranked.nth(7)), Some(&7));
+    assert_eq!(ranked.to_string(), "[0 >< 1 >< 2 >< 3 >< 4 >< 5 >< 6 >< 7 >< 8 >< 9]");
+}
+
+#[test]
+fn test_labeled_list() {
+    let mut rng = rand::thread_rng();
+    let mut list = LabeledIndexList::<u64>::new();
+    let mut model: Vec<ListIndex> = Vec::new();
+    let hot = list.insert_first(0);
+    model.push(hot);
+    for n in 1..20000 {
+        match rng.gen_range(0..8) {
+            // crowd the labels behind a single element to force relabeling
+            0..=2 => {
+                let at = model.iter().position(|&i| i == hot).unwrap();
+                model.insert(at + 1, list.insert_after(hot, n));
+            }
+            3 => model.insert(0, list.insert_first(n)),
+            4 => {
+                let pos = rng.gen_range(0..model.len());
+                let index = list.insert_before(model[pos], n);
+                model.insert(pos, index);
+            }
+            5 if model.len() > 1 => {
+                let pos = rng.gen_range(0..model.len());
+                if model[pos] != hot {
+                    list.remove(model.remove(pos));
+                }
+            }
+            6 if model.len() > 1 => {
+                let index = model.remove(rng.gen_range(0..model.len()));
+                let that = model[rng.gen_range(0..model.len())];
+                list.move_after(index, that);
+                let at = model.iter().position(|&i| i == that).unwrap();
+                model.insert(at + 1, index);
+            }
+            _ => {
+                let index = model.remove(rng.gen_range(0..model.len()));
+                list.move_to_first(index);
+                model.insert(0, index);
+            }
+        }
+        if n % 500 == 0 {
+            assert!(list.indexes().eq(model.iter().copied()));
+            for _ in 0..200 {
+                let a = rng.gen_range(0..model.len());
+                let b = rng.gen_range(0..model.len());
+                assert_eq!(list.compare_positions(model[a], model[b]), Some(a.cmp(&b)));
+            }
+        }
+    }
+    let removed = model.pop().unwrap();
+    list.remove(removed);
+    assert_eq!(list.compare_positions(removed, hot), None);
+    let labeled: LabeledIndexList<u64> = (0..100).collect();
+    let indexes: Vec<ListIndex> = labeled.indexes().collect();
+    assert!(indexes.windows(2).all(|w| labeled.compare_positions(w[0], w[1]) == Some(Ordering::Less)));
+}
+
+#[test]
+fn test_labeled_list_relabel_keeps_order() {
+    let mut list: LabeledIndexList<u64> = (0..6).collect();
+    let p = list.first_index();
+    let mut last = ListIndex::new();
+    for n in 0..200 {
+        let index = list.insert_after(p, n);
+        list.remove(last);
+        last = index;
+        let indexes: Vec<ListIndex> = list.indexes().collect();
+        for (a, &this) in indexes.iter().enumerate() {
+            for (b, &that) in indexes.iter().enumerate() {
+                assert_eq!(list.compare_positions(this, that), Some(a.cmp(&b)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_lru_cache() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&evicted);
+    let mut cache = LruCache::new(3);
+    cache.set_eviction_callback(move |key, value| log.lock().unwrap().push((key, value)));
+    for n in 0..3 {
+        assert_eq!(cache.put(n, n * 10), None);
+    }
+    assert_eq!(cache.get(&0), Some(&0));
+    *cache.get_mut(&1).unwrap() += 1;
+    assert_eq!(cache.peek(&2), Some(&20));
+    assert_eq!(cache.peek_lru(), Some((&2, &20)));
+    cache.put(3, 30);
+    assert_eq!(*evicted.lock().unwrap(), vec![(2, 20)]);
+    assert!(!cache.contains(&2));
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), vec![3, 1, 0]);
+    assert_eq!(cache.put(0, 5), Some(0));
+    assert_eq!(cache.pop_lru(), Some((1, 11)));
+    assert_eq!(cache.remove(&3), Some(30));
+    assert_eq!(cache.len(), 1);
+    cache.extend((10..14).map(|n| (n, n)));
+    assert_eq!(cache.len(), 3);
+    assert_eq!(evicted.lock().unwrap().len(), 3);
+    cache.resize(1);
+    assert_eq!(cache.capacity(), 1);
+    assert_eq!(cache.iter().collect::<Vec<_>>(), vec![(&13, &13)]);
+    assert_eq!(evicted.lock().unwrap().len(), 5);
+    cache.resize(0);
+    assert!(cache.is_empty());
+    cache.put(7, 7);
+    assert!(cache.is_empty());
+    assert_eq!(evicted.lock().unwrap().last(), Some(&(7, 7)));
+    let mut unbounded = LruCache::new(usize::MAX);
+    unbounded.put("key", 1);
+    assert_eq!(unbounded.get("key"), Some(&1));
+}
+
+#[test]
+fn test_lfu_cache() {
+    let mut rng = rand::thread_rng();
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&evicted);
+    let mut cache = LfuCache::new(16);
+    cache.set_eviction_callback(move |key, _| log.lock().unwrap().push(key));
+    // the model holds the key, frequency and time of last use of each entry
+    let mut model: Vec<(u32, u64, usize)> = Vec::new();
+    for tick in 0..5000 {
+        let key = rng.gen_range(0..40);
+        let found = model.iter().position(|&(k, _, _)| k == key);
+        if rng.gen_bool(0.5) {
+            assert_eq!(cache.get(&key).is_some(), found.is_some());
+            if let Some(at) = found {
+                model[at].1 += 1;
+                model[at].2 = tick;
+            }
+        } else {
+            cache.put(key, tick);
+            match found {
+                Some(at) => {
+                    model[at].1 += 1;
+                    model[at].2 = tick;
+                }
+                None => {
+                    if model.len() == 16 {
+                        let (at, _) = model
+                            .iter()
+                            .enumerate()
+                            .min_by_key(|(_, &(_, freq, used))| (freq, used))
+                            .unwrap();
+                        let (victim, _, _) = model.remove(at);
+                        assert_eq!(evicted.lock().unwrap().last(), Some(&victim));
+                    }
+                    model.push((key, 1, tick));
+                }
+            }
+        }
+        assert_eq!(cache.len(), model.len());
+        if let Some(&(key, freq, _)) = model.choose(&mut rng) {
+            assert_eq!(cache.frequency(&key), Some(freq));
+        }
+    }
+    model.sort_by_key(|&(_, freq, used)| std::cmp::Reverse((freq, used)));
+    assert!(cache.iter().map(|(k, _)| *k).eq(model.iter().map(|&(k, _, _)| k)));
+    let (lfu, _, _) = model.pop().unwrap();
+    assert_eq!(cache.peek_lfu().map(|(k, _)| *k), Some(lfu));
+    assert_eq!(cache.pop_lfu().map(|(k, _)| k), Some(lfu));
+    let (key, _, _) = model.remove(0);
+    assert!(cache.remove(&key).is_some());
+    assert!(cache.peek(&key).is_none());
+    cache.resize(4);
+    assert_eq!(cache.len(), 4);
+    assert!(cache.iter().map(|(k, _)| *k).eq(model.iter().take(4).map(|&(k, _, _)| k)));
+    let mut unbounded = LfuCache::new(usize::MAX);
+    unbounded.put("key", 1);
+    assert_eq!(unbounded.get("key"), Some(&1));
+}
+
+#[test]
+fn test_index_list_map() {
+    let mut map: IndexListMap<String, u32> = ["one", "two", "three"]
+        .iter()
+        .enumerate()
+        .map(|(n, s)| (s.to_string(), n as u32 + 1))
+        .collect();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["two"], 2);
+    assert_eq!(map.insert("one".into(), 10), Some(1));
+    assert_eq!(map.front(), Some((&"one".to_string(), &10)));
+    assert_eq!(map.insert_back("one".into(), 1), Some(10));
+    assert!(map.keys().eq(["two", "three", "one"].iter()));
+    assert!(map.move_to_front("one"));
+    assert!(!map.move_to_back("four"));
+    let index = map.index_of("three");
+    assert_eq!(map.as_list().prev_index(index), map.index_of("two"));
+    assert_eq!(map.get_index(index), Some((&"three".to_string(), &3)));
+    for (_, value) in map.iter_mut() {
+        *value *= 2;
+    }
+    assert!(map.values().eq([2, 4, 6].iter()));
+    match map.entry("four".into()) {
+        MapEntry::Vacant(entry) => *entry.insert_front(0) += 8,
+        MapEntry::Occupied(_) => panic!("four is not in the map"),
+    }
+    map.entry("two".into()).and_modify(|v| *v += 1).or_insert(0);
+    if let MapEntry::Occupied(mut entry) = map.entry("three".into()) {
+        entry.move_to_back();
+        assert_eq!(entry.insert(3), 6);
+    }
+    assert_eq!(format!("{:?}", map), r#"{"four": 8, "one": 2, "two": 5, "three": 3}"#);
+    assert_eq!(map.pop_front(), Some(("four".into(), 8)));
+    assert_eq!(map.pop_back(), Some(("three".into(), 3)));
+    assert_eq!(map.remove("one"), Some(2));
+    assert!(!map.contains_key("one"));
+    assert!(map.index_of("one").is_none());
+    if let MapEntry::Occupied(entry) = map.entry("two".into()) {
+        assert_eq!(entry.remove(), 5);
+    }
+    assert!(map.is_empty());
+    assert_eq!(map.back(), None);
+}
+
+#[test]
+fn test_index_list_set() {
+    let mut set: IndexListSet<u32> = [5, 3, 8, 3, 1].iter().copied().collect();
+    assert_eq!(set.len(), 4);
+    assert!(set.iter().eq([5, 3, 8, 1].iter()));
+    assert!(set.contains(&8));
+    assert_eq!(set.insert_full(3), (set.index_of(&3), false));
+    let (nine, added) = set.insert_full(9);
+    assert!(added);
+    assert_eq!(set.get_index(nine), Some(&9));
+    assert_eq!(set.insert_before(set.index_of(&3), 9), Some(nine));
+    assert!(set.iter().eq([5, 9, 3, 8, 1].iter()));
+    let two = set.insert_after(set.index_of(&8), 2).unwrap();
+    assert_eq!(set.as_list().next_index(set.index_of(&8)), two);
+    set.move_after(set.index_of(&5), two);
+    assert!(set.move_to_front(&1));
+    assert!(set.move_to_back(&9));
+    assert!(!set.move_to_back(&7));
+    assert_eq!(format!("{:?}", set), "{1, 3, 8, 2, 5, 9}");
+    assert_eq!(set.remove_index(two), Some(2));
+    assert_eq!(set.insert_before(two, 7), None);
+    assert_eq!(set.insert_after(two, 9), None);
+    assert!(!set.contains(&7));
+    assert!(set.index_of(&2).is_none());
+    assert!(set.remove(&8));
+    assert!(!set.remove(&8));
+    assert_eq!(set.take(&3), Some(3));
+    let other: IndexListSet<u32> = [9, 4, 1, 6].iter().copied().collect();
+    assert!(set.union(&other).eq([1, 5, 9, 4, 6].iter()));
+    assert!(set.intersection(&other).eq([1, 9].iter()));
+    assert!(set.difference(&other).eq([5].iter()));
+    assert_eq!(set.pop_front(), Some(1));
+    assert_eq!(set.pop_back(), Some(9));
+    assert_eq!(set.first(), set.last());
+    set.clear();
+    assert!(set.is_empty());
+}
+
+#[test]
+fn test_index_list_arena() {
+    let mut rng = rand::thread_rng();
+    let mut arena = IndexListArena::new();
+    let ids: Vec<_> = (0..8).map(|_| arena.new_list()).collect();
+    let mut model: Vec<Vec<ListIndex>> = vec![Vec::new(); ids.len()];
+    for n in 0..3000 {
+        let from = rng.gen_range(0..ids.len());
+        let to = rng.gen_range(0..ids.len());
+        let pick = rng.gen_range(0..model[from].len().max(1));
+        match rng.gen_range(0..6) {
+            0 => model[to].insert(0, arena.insert_first(ids[to], n)),
+            1 => model[to].push(arena.insert_last(ids[to], n)),
+            2 if !model[from].is_empty() => {
+                let pos = rng.gen_range(0..model[from].len());
+                let index = arena.insert_after(model[from][pos], n);
+                model[from].insert(pos + 1, index);
+            }
+            3 if !model[from].is_empty() => {
+                let index = model[from].remove(pick);
+                let elem = *arena.get(index).unwrap();
+                assert_eq!(arena.remove(index), Some(elem));
+            }
+            4 if !model[from].is_empty() => {
+                let index = model[from].remove(pick);
+                arena.move_to_last(index, ids[to]);
+                model[to].push(index);
+            }
+            _ if !model[from].is_empty() && !model[to].is_empty() => {
+                let index = model[from].remove(pick);
+                if model[to].is_empty() {
+                    model[from].push(index);
+                    arena.move_to_last(index, ids[from]);
+                } else {
+                    let pos = rng.gen_range(0..model[to].len());
+                    arena.move_before(index, model[to][pos]);
+                    model[to].insert(pos, index);
+                }
+            }
+            _ => (),
+        }
+        if n % 100 == 0 {
+            for (id, indexes) in ids.iter().zip(&model) {
+                assert_eq!(arena.list_len(*id), indexes.len());
+                let mut index = arena.first_index(*id);
+                for &expected in indexes {
+                    assert_eq!(index, expected);
+                    assert_eq!(arena.list_of(index), Some(*id));
+                    index = arena.next_index(index);
+                }
+                assert!(index.is_none());
+                assert!(arena.iter(*id).rev().eq(indexes.iter().rev().map(|&i| &arena[i])));
+            }
+            assert_eq!(arena.len(), model.iter().map(Vec::len).sum::<usize>());
+        }
+    }
+    // freed slots are shared by all the lists
+    let capacity = arena.capacity();
+    let before = arena.len();
+    assert!(arena.remove_list(ids[0]));
+    assert!(!arena.is_list(ids[0]));
+    for _ in 0..model[0].len() {
+        arena.insert_last(ids[1], 0);
+    }
+    assert_eq!(arena.capacity(), capacity);
+    assert_eq!(arena.len(), before);
+    let reused = arena.new_list();
+    assert_ne!(reused, ids[0]);
+    assert!(arena.is_list(reused));
+    assert!(!arena.is_list(ids[0]));
+    assert_eq!(arena.list_len(ids[0]), 0);
+    assert_eq!(arena.iter(ids[0]).count(), 0);
+    let stale = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        arena.insert_last(ids[0], 0);
+    }));
+    assert!(stale.is_err());
+    assert_eq!(arena.list_ids().count(), ids.len());
+}
+
+#[test]
+fn test_multi_index_list() {
+    let mut rng = rand::thread_rng();
+    let mut list = MultiIndexList::<u32, 3>::new();
+    let mut model: [Vec<ListIndex>; 3] = Default::default();
+    for n in 0..3000 {
+        let order = rng.gen_range(0..3);
+        match rng.gen_range(0..6) {
+            0 => {
+                let index = list.insert_last(n);
+                model.iter_mut().for_each(|m| m.push(index));
+            }
+            1 => {
+                let index = list.insert_first(n);
+                model.iter_mut().for_each(|m| m.insert(0, index));
+            }
+            2 if !model[0].is_empty() => {
+                let picks: Vec<usize> = model.iter().map(|m| rng.gen_range(0..m.len())).collect();
+                let positions = [
+                    ListPosition::Before(model[0][picks[0]]),
+                    ListPosition::After(model[1][picks[1]]),
+                    ListPosition::First,
+                ];
+                let index = list.insert(positions, n).unwrap();
+                model[0].insert(picks[0], index);
+                model[1].insert(picks[1] + 1, index);
+                model[2].insert(0, index);
+            }
+            3 if !model[0].is_empty() => {
+                let index = model[order][rng.gen_range(0..model[order].len())];
+                assert!(list.remove(index).is_some());
+                model.iter_mut().for_each(|m| m.retain(|&i| i != index));
+            }
+            4 if model[0].len() > 1 => {
+                let m = &mut model[order];
+                let index = m.remove(rng.gen_range(0..m.len()));
+                let pos = rng.gen_range(0..m.len());
+                assert!(list.move_after(order, index, m[pos]));
+                assert!(!list.move_before(order, index, ListIndex::new()));
+                m.insert(pos + 1, index);
+            }
+            _ if !model[0].is_empty() => {
+                let m = &mut model[order];
+                let index = m.remove(rng.gen_range(0..m.len()));
+                list.move_to_first(order, index);
+                m.insert(0, index);
+            }
+            _ => (),
+        }
+        assert_eq!(list.len(), model[0].len());
+        if n % 100 == 0 {
+            for (order, m) in model.iter().enumerate() {
+                assert!(list.indexes(order).eq(m.iter().copied()));
+                assert!(list.indexes(order).rev().eq(m.iter().rev().copied()));
+                assert!(list.iter(order).eq(m.iter().map(|&i| &list[i])));
+            }
+        }
+    }
+    assert!(list.insert([ListPosition::Before(ListIndex::from(9999u32)); 3], 0).is_err());
+    list.clear();
+    assert!(list.is_empty());
+    assert_eq!(format!("{:?}", list), "[[], [], []]");
+}
+
+#[test]
+fn test_index_tree() {
+    fn preorder(model: &HashMap<ListIndex, Vec<ListIndex>>, at: ListIndex, out: &mut Vec<ListIndex>) {
+        out.push(at);
+        for &child in &model[&at] {
+            preorder(model, child, out);
+        }
+    }
+    fn postorder(model: &HashMap<ListIndex, Vec<ListIndex>>, at: ListIndex, out: &mut Vec<ListIndex>) {
+        for &child in &model[&at] {
+            postorder(model, child, out);
+        }
+        out.push(at);
+    }
+    let mut rng = rand::thread_rng();
+    let mut tree = IndexTree::new();
+    let root = tree.new_root(0);
+    let mut model: HashMap<ListIndex, Vec<ListIndex>> = HashMap::new();
+    let mut parent: HashMap<ListIndex, ListIndex> = HashMap::new();
+    model.insert(root, Vec::new());
+    for n in 1..2000 {
+        let nodes: Vec<ListIndex> = model.keys().copied().collect();
+        let at = *nodes.choose(&mut rng).unwrap();
+        match rng.gen_range(0..6) {
+            0..=2 => {
+                let child = tree.append_child(at, n);
+                model.get_mut(&at).unwrap().push(child);
+                model.insert(child, Vec::new());
+                parent.insert(child, at);
+            }
+            3 if at != root => {
+                let up = parent[&at];
+                let before = rng.gen_bool(0.5);
+                let sibling = if before {
+                    tree.insert_sibling_before(at, n)
+                } else {
+                    tree.insert_sibling_after(at, n)
+                };
+                let children = model.get_mut(&up).unwrap();
+                let pos = children.iter().position(|&c| c == at).unwrap();
+                children.insert(if before { pos } else { pos + 1 }, sibling);
+                model.insert(sibling, Vec::new());
+                parent.insert(sibling, up);
+            }
+            4 if at != root && rng.gen_bool(0.3) => {
+                let mut gone = Vec::new();
+                preorder(&model, at, &mut gone);
+                let data = tree[at];
+                assert_eq!(tree.remove(at), Some(data));
+                model.get_mut(&parent[&at]).unwrap().retain(|&c| c != at);
+                for index in gone {
+                    model.remove(&index);
+                    parent.remove(&index);
+                    assert!(!tree.is_index_used(index));
+                }
+            }
+            _ if at != root => {
+                let target = *nodes.choose(&mut rng).unwrap();
+                let mut below = Vec::new();
+                preorder(&model, at, &mut below);
+                assert!(tree.detach(at));
+                assert_eq!(tree.last_root(), at);
+                if below.contains(&target) {
+                    assert!(!tree.reattach(at, target));
+                    assert!(tree.reattach(at, parent[&at]));
+                    let children = model.get_mut(&parent[&at]).unwrap();
+                    children.retain(|&c| c != at);
+                    children.push(at);
+                } else {
+                    assert!(tree.reattach(at, target));
+                    model.get_mut(&parent[&at]).unwrap().retain(|&c| c != at);
+                    model.get_mut(&target).unwrap().push(at);
+                    parent.insert(at, target);
+                }
+            }
+            _ => (),
+        }
+        assert_eq!(tree.len(), model.len());
+        if n % 100 == 0 {
+            let mut expected = Vec::new();
+            preorder(&model, root, &mut expected);
+            assert!(tree.preorder(root).eq(expected.iter().copied()));
+            expected.clear();
+            postorder(&model, root, &mut expected);
+            assert!(tree.postorder(root).eq(expected.iter().copied()));
+            let breadth: Vec<ListIndex> = tree.breadth_first(root).collect();
+            assert_eq!(breadth.len(), model.len());
+            assert!(breadth.windows(2).all(|w| tree.depth(w[0]) <= tree.depth(w[1])));
+            for (&index, children) in &model {
+                assert!(tree.children(index).eq(children.iter().copied()));
+                let mut up = index;
+                for ancestor in tree.ancestors(index) {
+                    assert_eq!(parent[&up], ancestor);
+                    up = ancestor;
+                }
+                assert_eq!(up, root);
+            }
+        }
+    }
+    assert!(tree.roots().eq([root]));
+    let leaf = tree.append_child(root, 0);
+    assert_eq!(tree.remove(leaf), Some(0));
+    assert_eq!(tree.preorder(leaf).count(), 0);
+    assert_eq!(tree.postorder(leaf).count(), 0);
+    assert_eq!(tree.breadth_first(leaf).count(), 0);
+    assert_eq!(tree.postorder(ListIndex::new()).count(), 0);
+}
+
+#[test]
+fn test_timer_wheel() {
+    let mut rng = rand::thread_rng();
+    let mut wheel = TimerWheel::new(1_000);
+    // the model holds the deadline, order of scheduling, handle and data of
+    // each timer
+    let mut model: Vec<(u64, usize, TimerHandle, usize)> = Vec::new();
+    for seq in 0..5000 {
+        let now = wheel.now();
+        let delay = match rng.gen_range(0..4) {
+            0 => rng.gen_range(0..64),
+            1 => rng.gen_range(0..5_000),
+            2 => rng.gen_range(0..1_000_000),
+            _ => rng.gen_range(0..u64::MAX / 2),
+        };
+        match rng.gen_range(0..10) {
+            0..=4 => {
+                let deadline = if rng.gen_bool(0.05) { now - 10 } else { now + delay };
+                model.push((deadline, seq, wheel.schedule(deadline, seq), seq));
+            }
+            5 if !model.is_empty() => {
+                let (_, _, handle, data) = model.swap_remove(rng.gen_range(0..model.len()));
+                assert_eq!(wheel.cancel(handle), Some(data));
+                assert_eq!(wheel.cancel(handle), None);
+            }
+            6 if !model.is_empty() => {
+                let pick = rng.gen_range(0..model.len());
+                let deadline = now + delay;
+                assert!(wheel.reschedule(model[pick].2, deadline));
+                model[pick].0 = deadline;
+                model[pick].1 = seq;
+            }
+            _ => {
+                let target = now + rng.gen_range(0..delay.min(100_000) + 1);
+                let expired = wheel.advance(target);
+                model.sort_by_key(|&(deadline, order, _, _)| (deadline, order));
+                let split = model.partition_point(|&(deadline, _, _, _)| deadline <= target);
+                let expected: Vec<_> = model.drain(..split).collect();
+                let expected: Vec<_> =
+                    expected.iter().map(|&(_, _, handle, data)| (handle, data)).collect();
+                assert_eq!(expired, expected);
+                assert_eq!(wheel.now(), target);
+            }
+        }
+        assert_eq!(wheel.len(), model.len());
+    }
+    for &(deadline, _, handle, _) in &model {
+        assert_eq!(wheel.deadline(handle), Some(deadline));
+    }
+    let expired = wheel.advance(u64::MAX);
+    assert_eq!(expired.len(), model.len());
+    assert!(wheel.is_empty());
+}